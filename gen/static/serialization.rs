@@ -15,3 +15,26 @@ impl<T: cbor_event::de::Deserialize> Deserialize for T {
         T::deserialize(raw).map_err(DeserializeError::from)
     }
 }
+
+/// Reads the value of a claim inlined in a map although it is not one of its fields
+fn deserialize_custom_claim<R: BufRead + Seek>(
+    raw: &mut Deserializer<R>,
+    read_len: &mut CBORReadLen,
+    custom: &mut OrderedHashMap<Keyy, Anyy>,
+    key: Keyy,
+) -> Result<(), DeserializeError> {
+    read_len.read_elems(1)?;
+    let (location, duplicate_key) = match &key {
+        Keyy::Int(Int::Uint { value, .. }) => (value.to_string(), Key::Uint(*value)),
+        Keyy::Int(int @ Int::Nint { value, .. }) => {
+            (int.to_string(), Key::Nint(-1 - i128::from(*value)))
+        }
+        Keyy::Text { text, .. } => (text.clone(), Key::Str(text.clone())),
+    };
+    if custom.contains_key(&key) {
+        return Err(DeserializeFailure::DuplicateKey(duplicate_key).into());
+    }
+    let value = Anyy::deserialize(raw).map_err(|e| e.annotate(location))?;
+    custom.insert(key, value);
+    Ok(())
+}
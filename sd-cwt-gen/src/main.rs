@@ -1,5 +1,19 @@
+//! Generates a reference copy of the `sd-cwt-types` crate from the CDDL in `gen/input`, offline
+//! once `cddl-codegen` has been fetched. It is never run by a build of `sd-cwt`, which only
+//! consumes the committed `sd-cwt-types`:
+//!
+//! ```sh
+//! cargo run --manifest-path sd-cwt-gen/Cargo.toml             # generate into target/sd-cwt-gen
+//! cargo run --manifest-path sd-cwt-gen/Cargo.toml -- --apply  # then update sd-cwt-types/rust
+//! ```
+//!
+//! The code is always generated into a scratch directory. Applying it only copies the files which
+//! changed and leaves alone the [HAND_MAINTAINED] ones, so that running it twice is a no-op. Most
+//! of the crate is maintained by hand, so CDDL changes mostly have to be merged from the scratch
+//! directory.
+
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use cddl_codegen::{
     cli::Cli,
@@ -11,11 +25,51 @@ use cddl_codegen::{
     parsing::{parse_rule, rule_ident, rule_is_scope_marker},
 };
 
+/// Files of `sd-cwt-types/rust` which were written or patched by hand after generation e.g. the
+/// encoding-preserving `Anyy`, the serialization of the inlined custom claims and the builders.
+/// Changes to their generated version have to be merged manually.
+const HAND_MAINTAINED: [&str; 6] = [
+    "Cargo.toml",
+    "src/anyy.rs",
+    "src/builder.rs",
+    "src/cbor_encodings.rs",
+    "src/lib.rs",
+    "src/serialization.rs",
+];
+
 fn main() -> Result<(), Box<dyn Error>> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let types_dir = root.join("sd-cwt-types");
+
+    let mut apply = false;
+    let mut out = root.join("target").join("sd-cwt-gen");
+    let mut cli_args = std::env::args().skip(1);
+    while let Some(arg) = cli_args.next() {
+        match arg.as_str() {
+            "--apply" => apply = true,
+            "--out" => {
+                out = cli_args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or("--out expects a directory")?
+            }
+            _ => return Err(format!("Unknown argument {arg}, expected --apply or --out").into()),
+        }
+    }
+
+    // the scratch directory belongs to the generator, recreating it keeps runs reproducible
+    if out.exists() {
+        if out.canonicalize()?.starts_with(types_dir.canonicalize()?) {
+            return Err("The output directory cannot be within sd-cwt-types".into());
+        }
+        std::fs::remove_dir_all(&out)?;
+    }
+    std::fs::create_dir_all(&out)?;
+
     let args = Cli {
-        input: PathBuf::from("../gen/input"),
-        output: PathBuf::from("../sd-cwt-types"),
-        static_dir: PathBuf::from("../gen/static"),
+        input: root.join("gen/input"),
+        output: out.clone(),
+        static_dir: root.join("gen/static"),
         lib_name: "sd-cwt-types".to_string(),
         annotate_fields: true,
         to_from_bytes_methods: true,
@@ -30,12 +84,58 @@ fn main() -> Result<(), Box<dyn Error>> {
         wasm_cbor_json_api_macro: None,
         wasm_conversions_macro: None,
     };
+    gen(args)?;
 
-    std::fs::remove_dir_all("../sd-cwt-types")?;
-    std::fs::create_dir_all("../sd-cwt-types")?;
+    let generated = out.join("rust");
+    if apply {
+        apply_generated(&generated, &types_dir.join("rust"))?;
+    } else {
+        println!(
+            "Generated into {}, run with --apply to update sd-cwt-types",
+            generated.display()
+        );
+    }
+    Ok(())
+}
 
-    gen(args)?;
+/// Copies the generated files which differ from the committed ones, except the hand-maintained
+/// ones which are only reported
+fn apply_generated(generated: &Path, committed: &Path) -> Result<(), Box<dyn Error>> {
+    let mut files = Vec::new();
+    generated_files(&mut files, generated)?;
+    for file in files {
+        let relative = pathdiff::diff_paths(&file, generated).ok_or("Unexpected generated file")?;
+        let target = committed.join(&relative);
+        let content = std::fs::read(&file)?;
+        if std::fs::read(&target).ok().as_ref() == Some(&content) {
+            continue;
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if HAND_MAINTAINED.contains(&relative.as_str()) {
+            println!(
+                "{relative} is maintained by hand, merge {} manually",
+                file.display()
+            );
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, content)?;
+        println!("Updated {relative}");
+    }
+    Ok(())
+}
 
+fn generated_files(output: &mut Vec<PathBuf>, dir: &Path) -> std::io::Result<()> {
+    for dir_entry in std::fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        if path.is_dir() {
+            generated_files(output, &path)?;
+        } else {
+            output.push(path);
+        }
+    }
     Ok(())
 }
 
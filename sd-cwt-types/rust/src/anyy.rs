@@ -0,0 +1,619 @@
+// This file is NOT code-generated: `anyy` is declared as an extern type in the CDDL since
+// cddl-codegen cannot represent an arbitrary data item while preserving its encoding.
+
+use crate::error::*;
+use crate::serialization::*;
+use crate::Int;
+use cbor_event::de::Deserializer;
+use cbor_event::se::Serializer;
+use std::convert::{TryFrom, TryInto};
+use std::io::{BufRead, Seek, Write};
+
+/// Any CBOR data item, keeping track of how it was encoded so that it can be re-encoded
+/// byte for byte
+#[derive(Clone, Debug, derivative::Derivative)]
+#[derivative(PartialEq)]
+pub enum Anyy {
+    Int(Int),
+    Bytes {
+        bytes: Vec<u8>,
+        #[derivative(PartialEq = "ignore")]
+        bytes_encoding: StringEncoding,
+    },
+    Text {
+        text: String,
+        #[derivative(PartialEq = "ignore")]
+        text_encoding: StringEncoding,
+    },
+    Array {
+        elements: Vec<Anyy>,
+        #[derivative(PartialEq = "ignore")]
+        len_encoding: LenEncoding,
+    },
+    Map {
+        entries: Vec<(Anyy, Anyy)>,
+        #[derivative(PartialEq = "ignore")]
+        len_encoding: LenEncoding,
+    },
+    Tag {
+        tag: u64,
+        value: Box<Anyy>,
+        #[derivative(PartialEq = "ignore")]
+        tag_encoding: Option<cbor_event::Sz>,
+    },
+    Float {
+        value: f64,
+        /// `Two`, `Four` or `Eight` for half, single or double precision
+        #[derivative(PartialEq = "ignore")]
+        float_encoding: Option<cbor_event::Sz>,
+    },
+    Bool(bool),
+    Null,
+    Undefined,
+    Simple(u8),
+}
+
+impl Anyy {
+    pub fn new_int(int: Int) -> Self {
+        Self::Int(int)
+    }
+
+    pub fn new_bytes(bytes: Vec<u8>) -> Self {
+        Self::Bytes {
+            bytes,
+            bytes_encoding: StringEncoding::default(),
+        }
+    }
+
+    pub fn new_text(text: String) -> Self {
+        Self::Text {
+            text,
+            text_encoding: StringEncoding::default(),
+        }
+    }
+
+    pub fn new_array(elements: Vec<Anyy>) -> Self {
+        Self::Array {
+            elements,
+            len_encoding: LenEncoding::default(),
+        }
+    }
+
+    pub fn new_map(entries: Vec<(Anyy, Anyy)>) -> Self {
+        Self::Map {
+            entries,
+            len_encoding: LenEncoding::default(),
+        }
+    }
+
+    pub fn new_tag(tag: u64, value: Anyy) -> Self {
+        Self::Tag {
+            tag,
+            value: Box::new(value),
+            tag_encoding: None,
+        }
+    }
+
+    pub fn new_float(value: f64) -> Self {
+        Self::Float {
+            value,
+            float_encoding: None,
+        }
+    }
+}
+
+impl cbor_event::se::Serialize for Anyy {
+    fn serialize<'se, W: Write>(
+        &self,
+        serializer: &'se mut Serializer<W>,
+    ) -> cbor_event::Result<&'se mut Serializer<W>> {
+        match self {
+            Anyy::Int(int) => int.serialize(serializer),
+            Anyy::Bytes {
+                bytes,
+                bytes_encoding,
+            } => serializer.write_bytes_sz(bytes, bytes_encoding.to_str_len_sz(bytes.len() as u64)),
+            Anyy::Text {
+                text,
+                text_encoding,
+            } => serializer.write_text_sz(text, text_encoding.to_str_len_sz(text.len() as u64)),
+            Anyy::Array {
+                elements,
+                len_encoding,
+            } => {
+                serializer.write_array_sz(len_encoding.to_len_sz(elements.len() as u64))?;
+                for element in elements {
+                    element.serialize(serializer)?;
+                }
+                len_encoding.end(serializer)
+            }
+            Anyy::Map {
+                entries,
+                len_encoding,
+            } => {
+                serializer.write_map_sz(len_encoding.to_len_sz(entries.len() as u64))?;
+                for (key, value) in entries {
+                    key.serialize(serializer)?;
+                    value.serialize(serializer)?;
+                }
+                len_encoding.end(serializer)
+            }
+            Anyy::Tag {
+                tag,
+                value,
+                tag_encoding,
+            } => {
+                serializer.write_tag_sz(*tag, fit_sz(*tag, *tag_encoding))?;
+                value.serialize(serializer)
+            }
+            Anyy::Float {
+                value,
+                float_encoding,
+            } => serializer.write_raw_bytes(&encode_float(*value, *float_encoding)),
+            Anyy::Bool(b) => serializer.write_special(cbor_event::Special::Bool(*b)),
+            Anyy::Null => serializer.write_special(cbor_event::Special::Null),
+            Anyy::Undefined => serializer.write_special(cbor_event::Special::Undefined),
+            Anyy::Simple(value) => {
+                serializer.write_special(cbor_event::Special::Unassigned(*value))
+            }
+        }
+    }
+}
+
+impl Deserialize for Anyy {
+    fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
+        (|| -> Result<_, DeserializeError> {
+            match raw.cbor_type()? {
+                cbor_event::Type::UnsignedInteger | cbor_event::Type::NegativeInteger => {
+                    Ok(Anyy::Int(Int::deserialize(raw)?))
+                }
+                cbor_event::Type::Bytes => {
+                    let (bytes, bytes_encoding) = raw.bytes_sz()?;
+                    Ok(Anyy::Bytes {
+                        bytes,
+                        bytes_encoding: bytes_encoding.into(),
+                    })
+                }
+                cbor_event::Type::Text => {
+                    let (text, text_encoding) = raw.text_sz()?;
+                    Ok(Anyy::Text {
+                        text,
+                        text_encoding: text_encoding.into(),
+                    })
+                }
+                cbor_event::Type::Array => {
+                    let len = raw.array_sz()?;
+                    let mut elements = vec![];
+                    while has_next(raw, len, elements.len())? {
+                        elements.push(Anyy::deserialize(raw)?);
+                    }
+                    Ok(Anyy::Array {
+                        elements,
+                        len_encoding: len.into(),
+                    })
+                }
+                cbor_event::Type::Map => {
+                    let len = raw.map_sz()?;
+                    let mut entries = vec![];
+                    while has_next(raw, len, entries.len())? {
+                        let key = Anyy::deserialize(raw)?;
+                        let value = Anyy::deserialize(raw)?;
+                        entries.push((key, value));
+                    }
+                    Ok(Anyy::Map {
+                        entries,
+                        len_encoding: len.into(),
+                    })
+                }
+                cbor_event::Type::Tag => {
+                    let (tag, tag_encoding) = raw.tag_sz()?;
+                    Ok(Anyy::Tag {
+                        tag,
+                        value: Box::new(Anyy::deserialize(raw)?),
+                        tag_encoding: Some(tag_encoding),
+                    })
+                }
+                cbor_event::Type::Special => deserialize_special(raw),
+            }
+        })()
+        .map_err(|e| e.annotate("Anyy"))
+    }
+}
+
+/// Whether another element follows in an array or map, consuming the break of an indefinite one
+fn has_next<R: BufRead + Seek>(
+    raw: &mut Deserializer<R>,
+    len: cbor_event::LenSz,
+    read: usize,
+) -> Result<bool, DeserializeError> {
+    match len {
+        cbor_event::LenSz::Len(n, _) => Ok((read as u64) < n),
+        cbor_event::LenSz::Indefinite => {
+            Ok(!(raw.cbor_type()? == cbor_event::Type::Special && raw.special_break()?))
+        }
+    }
+}
+
+fn deserialize_special<R: BufRead + Seek>(
+    raw: &mut Deserializer<R>,
+) -> Result<Anyy, DeserializeError> {
+    // cbor_event reads every float as a double and mangles half-precision ones so floats are
+    // decoded here to also remember their precision
+    let initial_byte = raw
+        .as_mut_ref()
+        .fill_buf()
+        .map_err(cbor_event::Error::from)?
+        .first()
+        .copied();
+    let float_encoding = match initial_byte {
+        Some(0xf9) => Some(cbor_event::Sz::Two),
+        Some(0xfa) => Some(cbor_event::Sz::Four),
+        Some(0xfb) => Some(cbor_event::Sz::Eight),
+        _ => None,
+    };
+    if let Some(sz) = float_encoding {
+        let mut buf = vec![0u8; 1 + sz.bytes_following()];
+        raw.as_mut_ref()
+            .read_exact(&mut buf)
+            .map_err(cbor_event::Error::from)?;
+        return Ok(Anyy::Float {
+            value: decode_float(&buf[1..]),
+            float_encoding: Some(sz),
+        });
+    }
+    match raw.special()? {
+        cbor_event::Special::Bool(b) => Ok(Anyy::Bool(b)),
+        cbor_event::Special::Null => Ok(Anyy::Null),
+        cbor_event::Special::Undefined => Ok(Anyy::Undefined),
+        cbor_event::Special::Unassigned(value) => Ok(Anyy::Simple(value)),
+        cbor_event::Special::Float(value) => Ok(Anyy::new_float(value)),
+        cbor_event::Special::Break => Err(DeserializeFailure::BreakInDefiniteLen.into()),
+    }
+}
+
+fn decode_float(bytes: &[u8]) -> f64 {
+    match *bytes {
+        [a, b] => f16_to_f64(u16::from_be_bytes([a, b])),
+        [a, b, c, d] => f32::from_be_bytes([a, b, c, d]) as f64,
+        [a, b, c, d, e, f, g, h] => f64::from_be_bytes([a, b, c, d, e, f, g, h]),
+        _ => unreachable!("a float is 2, 4 or 8 bytes long"),
+    }
+}
+
+/// Encodes a float with the given precision, falling back to the shortest lossless one
+fn encode_float(value: f64, sz: Option<cbor_event::Sz>) -> Vec<u8> {
+    let half = f64_to_f16(value);
+    let single = Some(value as f32).filter(|f| *f as f64 == value || value.is_nan());
+    match (sz, half, single) {
+        (Some(cbor_event::Sz::Two), Some(half), _) | (None, Some(half), _) => {
+            [&[0xf9][..], &half.to_be_bytes()].concat()
+        }
+        (Some(cbor_event::Sz::Two), None, Some(single))
+        | (Some(cbor_event::Sz::Four), _, Some(single))
+        | (None, None, Some(single)) => [&[0xfa][..], &single.to_be_bytes()].concat(),
+        _ => [&[0xfb][..], &value.to_be_bytes()].concat(),
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let sign = if half & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1024.0 + mantissa) * 2f64.powi(exponent - 25),
+    }
+}
+
+/// Half-precision bits of the float, `None` when it cannot be represented exactly
+fn f64_to_f16(value: f64) -> Option<u16> {
+    if value.is_nan() {
+        return Some(0x7e00);
+    }
+    let single = value as f32;
+    if single as f64 != value {
+        return None;
+    }
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+    match exponent {
+        // zero
+        -127 if mantissa == 0 => Some(sign),
+        // infinity
+        128 => Some(sign | 0x7c00),
+        -14..=15 if mantissa & 0x1fff == 0 => {
+            Some(sign | (((exponent + 15) as u16) << 10) | (mantissa >> 13) as u16)
+        }
+        // subnormal half
+        -24..=-15 => {
+            let shift = (-(exponent + 1)) as u32;
+            let mantissa = mantissa | 0x80_0000;
+            (mantissa & ((1 << shift) - 1) == 0).then(|| sign | (mantissa >> shift) as u16)
+        }
+        _ => None,
+    }
+}
+
+fn int_to_i128(int: &Int) -> i128 {
+    match int {
+        Int::Uint { value, .. } => *value as i128,
+        Int::Nint { value, .. } => -(*value as i128) - 1,
+    }
+}
+
+/// Reasons why an arbitrary CBOR data item has no counterpart in another data model
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnyyConversionError {
+    Bytes,
+    Tag(u64),
+    NonTextKey,
+    NonFiniteFloat,
+    /// Integer below `i64::MIN`, which serde_json cannot represent
+    IntOutOfRange(i128),
+    Undefined,
+    Simple(u8),
+}
+
+impl std::fmt::Display for AnyyConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes => write!(f, "byte strings are not supported"),
+            Self::Tag(tag) => write!(f, "tag {} is not supported", tag),
+            Self::NonTextKey => write!(f, "map keys must be text"),
+            Self::NonFiniteFloat => write!(f, "floats must be finite"),
+            Self::IntOutOfRange(int) => write!(f, "integer {} is out of range", int),
+            Self::Undefined => write!(f, "undefined is not supported"),
+            Self::Simple(value) => write!(f, "simple value {} is not supported", value),
+        }
+    }
+}
+
+impl std::error::Error for AnyyConversionError {}
+
+impl From<ciborium::Value> for Anyy {
+    fn from(value: ciborium::Value) -> Self {
+        match value {
+            ciborium::Value::Integer(i) => {
+                // a CBOR integer always fits in an Int
+                Anyy::Int(Int::try_from(i128::from(i)).unwrap())
+            }
+            ciborium::Value::Bytes(bytes) => Anyy::new_bytes(bytes),
+            ciborium::Value::Float(f) => Anyy::new_float(f),
+            ciborium::Value::Text(text) => Anyy::new_text(text),
+            ciborium::Value::Bool(b) => Anyy::Bool(b),
+            ciborium::Value::Null => Anyy::Null,
+            ciborium::Value::Tag(tag, value) => Anyy::new_tag(tag, Anyy::from(*value)),
+            ciborium::Value::Array(elements) => {
+                Anyy::new_array(elements.into_iter().map(Anyy::from).collect())
+            }
+            ciborium::Value::Map(entries) => Anyy::new_map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Anyy::from(k), Anyy::from(v)))
+                    .collect(),
+            ),
+            _ => unreachable!("every ciborium value variant is covered"),
+        }
+    }
+}
+
+impl TryFrom<Anyy> for ciborium::Value {
+    type Error = AnyyConversionError;
+
+    fn try_from(value: Anyy) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Anyy::Int(int) => {
+                // a CBOR integer always fits in a ciborium Integer
+                ciborium::Value::Integer(
+                    ciborium::value::Integer::try_from(int_to_i128(&int)).unwrap(),
+                )
+            }
+            Anyy::Bytes { bytes, .. } => ciborium::Value::Bytes(bytes),
+            Anyy::Text { text, .. } => ciborium::Value::Text(text),
+            Anyy::Array { elements, .. } => ciborium::Value::Array(
+                elements
+                    .into_iter()
+                    .map(ciborium::Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Anyy::Map { entries, .. } => ciborium::Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| Ok((k.try_into()?, v.try_into()?)))
+                    .collect::<Result<_, AnyyConversionError>>()?,
+            ),
+            Anyy::Tag { tag, value, .. } => {
+                ciborium::Value::Tag(tag, Box::new(ciborium::Value::try_from(*value)?))
+            }
+            Anyy::Float { value, .. } => ciborium::Value::Float(value),
+            Anyy::Bool(b) => ciborium::Value::Bool(b),
+            Anyy::Null => ciborium::Value::Null,
+            Anyy::Undefined => return Err(AnyyConversionError::Undefined),
+            Anyy::Simple(value) => return Err(AnyyConversionError::Simple(value)),
+        })
+    }
+}
+
+impl From<serde_json::Value> for Anyy {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Anyy::Null,
+            serde_json::Value::Bool(b) => Anyy::Bool(b),
+            serde_json::Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    Anyy::Int(Int::new_uint(u))
+                } else if let Some(i) = n.as_i64() {
+                    Anyy::Int(Int::try_from(i as i128).unwrap())
+                } else {
+                    // without arbitrary precision, a JSON number is either an integer or a float
+                    Anyy::new_float(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(text) => Anyy::new_text(text),
+            serde_json::Value::Array(elements) => {
+                Anyy::new_array(elements.into_iter().map(Anyy::from).collect())
+            }
+            serde_json::Value::Object(entries) => Anyy::new_map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Anyy::new_text(k), Anyy::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<Anyy> for serde_json::Value {
+    type Error = AnyyConversionError;
+
+    fn try_from(value: Anyy) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Anyy::Int(Int::Uint { value, .. }) => value.into(),
+            Anyy::Int(int) => {
+                let int = int_to_i128(&int);
+                // JSON numbers are arbitrary but serde_json stops at i64
+                i64::try_from(int)
+                    .map_err(|_| AnyyConversionError::IntOutOfRange(int))?
+                    .into()
+            }
+            Anyy::Text { text, .. } => serde_json::Value::String(text),
+            Anyy::Array { elements, .. } => serde_json::Value::Array(
+                elements
+                    .into_iter()
+                    .map(serde_json::Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Anyy::Map { entries, .. } => serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| match k {
+                        Anyy::Text { text, .. } => Ok((text, v.try_into()?)),
+                        _ => Err(AnyyConversionError::NonTextKey),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Anyy::Float { value, .. } => serde_json::Number::from_f64(value)
+                .ok_or(AnyyConversionError::NonFiniteFloat)?
+                .into(),
+            Anyy::Bool(b) => serde_json::Value::Bool(b),
+            Anyy::Null => serde_json::Value::Null,
+            Anyy::Bytes { .. } => return Err(AnyyConversionError::Bytes),
+            Anyy::Tag { tag, .. } => return Err(AnyyConversionError::Tag(tag)),
+            Anyy::Undefined => return Err(AnyyConversionError::Undefined),
+            Anyy::Simple(value) => return Err(AnyyConversionError::Simple(value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::ToCBORBytes;
+
+    fn round_trip(bytes: &[u8]) -> Anyy {
+        let anyy = Anyy::from_cbor_bytes(bytes).unwrap();
+        assert_eq!(anyy.to_cbor_bytes(), bytes);
+        anyy
+    }
+
+    #[test]
+    fn should_round_trip_non_canonical_encodings() {
+        // 1 encoded on 2 bytes
+        round_trip(&[0x19, 0x00, 0x01]);
+        // indefinite text "ab" in 2 chunks
+        round_trip(&[0x7f, 0x61, 0x61, 0x61, 0x62, 0xff]);
+        // indefinite array [1, h'01']
+        round_trip(&[0x9f, 0x01, 0x41, 0x01, 0xff]);
+        // indefinite map {"a": null}
+        round_trip(&[0xbf, 0x61, 0x61, 0xf6, 0xff]);
+        // tag 1 encoded on 1 byte
+        round_trip(&[0xd8, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn should_round_trip_specials() {
+        assert_eq!(round_trip(&[0xf4]), Anyy::Bool(false));
+        assert_eq!(round_trip(&[0xf7]), Anyy::Undefined);
+        assert_eq!(round_trip(&[0xf8, 0xff]), Anyy::Simple(255));
+        assert_eq!(round_trip(&[0xf9, 0x3e, 0x00]), Anyy::new_float(1.5));
+        assert_eq!(
+            round_trip(&[0xfa, 0x3f, 0xc0, 0x00, 0x00]),
+            Anyy::new_float(1.5)
+        );
+        assert_eq!(
+            round_trip(&[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0]),
+            Anyy::new_float(1.5)
+        );
+        assert_eq!(
+            round_trip(&[0xf9, 0x00, 0x01]),
+            Anyy::new_float(2f64.powi(-24))
+        );
+    }
+
+    #[test]
+    fn should_encode_floats_in_shortest_form() {
+        assert_eq!(Anyy::new_float(-0.0).to_cbor_bytes(), [0xf9, 0x80, 0x00]);
+        assert_eq!(
+            Anyy::new_float(100000.0).to_cbor_bytes(),
+            [0xfa, 0x47, 0xc3, 0x50, 0x00]
+        );
+        assert_eq!(Anyy::new_float(1.1).to_cbor_bytes()[0], 0xfb);
+    }
+
+    #[test]
+    fn should_match_ciborium() {
+        let value = ciborium::Value::Map(vec![
+            (
+                "a".into(),
+                ciborium::Value::Array(vec![(-1000).into(), 2.5.into()]),
+            ),
+            (
+                1.into(),
+                ciborium::Value::Tag(0, Box::new(ciborium::Value::Bytes(vec![1]))),
+            ),
+        ]);
+        let mut bytes = vec![];
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+
+        let anyy = Anyy::from(value.clone());
+        assert_eq!(anyy.to_cbor_bytes(), bytes);
+        assert_eq!(ciborium::Value::try_from(anyy).unwrap(), value);
+    }
+
+    #[test]
+    fn should_convert_json() {
+        let json = serde_json::json!({ "a": [1, -2, 2.5, "b", null, true] });
+        let anyy = Anyy::from(json.clone());
+        assert_eq!(serde_json::Value::try_from(anyy).unwrap(), json);
+    }
+
+    #[test]
+    fn should_fail_converting_cbor_only_values_to_json() {
+        let to_json = |anyy: Anyy| serde_json::Value::try_from(anyy).unwrap_err();
+        assert_eq!(
+            to_json(Anyy::new_bytes(vec![1])),
+            AnyyConversionError::Bytes
+        );
+        assert_eq!(
+            to_json(Anyy::new_tag(1, Anyy::Null)),
+            AnyyConversionError::Tag(1)
+        );
+        assert_eq!(
+            to_json(Anyy::new_float(f64::NAN)),
+            AnyyConversionError::NonFiniteFloat
+        );
+        assert_eq!(
+            to_json(Anyy::new_map(vec![(Anyy::Null, Anyy::Null)])),
+            AnyyConversionError::NonTextKey
+        );
+        assert_eq!(to_json(Anyy::Undefined), AnyyConversionError::Undefined);
+        assert_eq!(
+            to_json(Anyy::Int(Int::new_nint(u64::MAX))),
+            AnyyConversionError::IntOutOfRange(-(u64::MAX as i128) - 1)
+        );
+    }
+}
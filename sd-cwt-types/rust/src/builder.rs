@@ -0,0 +1,354 @@
+//! Fluent builders of [SdPayload] and [SdProtected] taking native Rust types
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::ordered_hash_map::OrderedHashMap;
+use crate::{Anyy, Int, Keyy, SdPayload, SdProtected};
+
+/// Labels of the claims set directly by [SdPayloadBuilder]
+const PAYLOAD_LABELS: [i64; 11] = [1, 2, 3, 4, 5, 6, 8, 39, 1113, 1114, 1115];
+const AUD_LABEL: i64 = 3;
+
+/// Labels of the headers set directly by [SdProtectedBuilder]
+const PROTECTED_LABELS: [i64; 2] = [ALG_LABEL, TYP_LABEL];
+const ALG_LABEL: i64 = 1;
+const TYP_LABEL: i64 = 16;
+
+/// Reasons why a builder cannot produce its value
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuilderError {
+    /// Mandatory claim or header, by label, which was never set
+    MissingField(i64),
+    /// Label of a claim or header that has its own setter
+    ReservedLabel(i64),
+}
+
+impl std::fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingField(label) => write!(f, "mandatory field {} is missing", label),
+            Self::ReservedLabel(label) => {
+                write!(f, "label {} has to be set through its own setter", label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// Seconds since the Unix epoch, as in the time claims of a CWT
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NumericDate(pub i64);
+
+impl NumericDate {
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+}
+
+impl From<i64> for NumericDate {
+    fn from(secs: i64) -> Self {
+        Self(secs)
+    }
+}
+
+impl From<SystemTime> for NumericDate {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Self(since.as_secs() as i64),
+            Err(before) => Self(-(before.duration().as_secs() as i64)),
+        }
+    }
+}
+
+impl From<NumericDate> for Int {
+    fn from(date: NumericDate) -> Self {
+        Int::from(date.0)
+    }
+}
+
+impl From<i64> for Int {
+    fn from(x: i64) -> Self {
+        // an i64 always fits in a CBOR int
+        Int::try_from(x as i128).unwrap()
+    }
+}
+
+impl From<i64> for Keyy {
+    fn from(label: i64) -> Self {
+        Keyy::new_int(label.into())
+    }
+}
+
+impl From<&str> for Keyy {
+    fn from(name: &str) -> Self {
+        Keyy::new_text(name.to_string())
+    }
+}
+
+impl From<String> for Keyy {
+    fn from(name: String) -> Self {
+        Keyy::new_text(name)
+    }
+}
+
+/// Returns the first key that is one of `labels`
+fn reserved(custom: &OrderedHashMap<Keyy, Anyy>, labels: &[i64]) -> Option<i64> {
+    labels
+        .iter()
+        .copied()
+        .find(|label| custom.contains_key(&Keyy::from(*label)))
+}
+
+#[derive(Clone, Debug)]
+enum Expiration {
+    At(NumericDate),
+    In(Duration),
+}
+
+/// Builds an [SdPayload], see [SdPayload::builder]
+#[derive(Clone, Debug)]
+pub struct SdPayloadBuilder {
+    iss: Option<String>,
+    sub: Option<String>,
+    aud: Option<String>,
+    exp: Option<Expiration>,
+    nbf: Option<NumericDate>,
+    iat: Option<NumericDate>,
+    cnonce: Option<Vec<u8>>,
+    cnf: Option<OrderedHashMap<Keyy, Anyy>>,
+    sd_hash: Option<Vec<u8>>,
+    sd_alg: Option<i64>,
+    custom: OrderedHashMap<Keyy, Anyy>,
+}
+
+impl SdPayload {
+    /// Starts building a payload. `aud` is mandatory whereas `iat` defaults to the time
+    /// [SdPayloadBuilder::build] is called.
+    pub fn builder() -> SdPayloadBuilder {
+        SdPayloadBuilder {
+            iss: None,
+            sub: None,
+            aud: None,
+            exp: None,
+            nbf: None,
+            iat: None,
+            cnonce: None,
+            cnf: None,
+            sd_hash: None,
+            sd_alg: None,
+            custom: OrderedHashMap::new(),
+        }
+    }
+}
+
+impl SdPayloadBuilder {
+    pub fn issuer(mut self, iss: impl Into<String>) -> Self {
+        self.iss = Some(iss.into());
+        self
+    }
+
+    pub fn subject(mut self, sub: impl Into<String>) -> Self {
+        self.sub = Some(sub.into());
+        self
+    }
+
+    pub fn audience(mut self, aud: impl Into<String>) -> Self {
+        self.aud = Some(aud.into());
+        self
+    }
+
+    pub fn expires_at(mut self, exp: impl Into<NumericDate>) -> Self {
+        self.exp = Some(Expiration::At(exp.into()));
+        self
+    }
+
+    /// Sets `exp` relative to `iat`
+    pub fn expires_in(mut self, validity: Duration) -> Self {
+        self.exp = Some(Expiration::In(validity));
+        self
+    }
+
+    pub fn not_before(mut self, nbf: impl Into<NumericDate>) -> Self {
+        self.nbf = Some(nbf.into());
+        self
+    }
+
+    pub fn issued_at(mut self, iat: impl Into<NumericDate>) -> Self {
+        self.iat = Some(iat.into());
+        self
+    }
+
+    pub fn cnonce(mut self, cnonce: impl Into<Vec<u8>>) -> Self {
+        self.cnonce = Some(cnonce.into());
+        self
+    }
+
+    /// Confirmation of the holder key, as a map with a single entry e.g. `{1: COSE_Key}`
+    pub fn cnf(mut self, cnf: OrderedHashMap<Keyy, Anyy>) -> Self {
+        self.cnf = Some(cnf);
+        self
+    }
+
+    pub fn sd_hash(mut self, sd_hash: impl Into<Vec<u8>>) -> Self {
+        self.sd_hash = Some(sd_hash.into());
+        self
+    }
+
+    /// COSE algorithm the disclosures are hashed with e.g. -16 for SHA-256
+    pub fn sd_alg(mut self, alg: i64) -> Self {
+        self.sd_alg = Some(alg);
+        self
+    }
+
+    /// Adds any other claim, by label or by name. The value is anything convertible to CBOR e.g.
+    /// a `serde_json::Value` or a `ciborium::Value`.
+    pub fn claim(mut self, key: impl Into<Keyy>, value: impl Into<Anyy>) -> Self {
+        self.custom.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SdPayload, BuilderError> {
+        if let Some(label) = reserved(&self.custom, &PAYLOAD_LABELS) {
+            return Err(BuilderError::ReservedLabel(label));
+        }
+        let aud = self.aud.ok_or(BuilderError::MissingField(AUD_LABEL))?;
+        let iat = self.iat.unwrap_or_else(NumericDate::now);
+        let exp = self.exp.map(|exp| match exp {
+            Expiration::At(exp) => exp,
+            Expiration::In(validity) => {
+                NumericDate(iat.0.saturating_add(validity.as_secs() as i64))
+            }
+        });
+
+        let mut payload = SdPayload::new(aud, iat.into(), self.custom);
+        payload.iss = self.iss;
+        payload.sub = self.sub;
+        payload.exp = exp.map(Int::from);
+        payload.nbf = self.nbf.map(Int::from);
+        payload.cnonce = self.cnonce;
+        payload.cnf = self.cnf;
+        payload.sd_hash = self.sd_hash;
+        payload.sd_alg = self.sd_alg.map(Int::from);
+        Ok(payload)
+    }
+}
+
+/// Builds an [SdProtected], see [SdProtected::builder]
+#[derive(Clone, Debug)]
+pub struct SdProtectedBuilder {
+    alg: Option<i64>,
+    typ: Option<String>,
+    custom: OrderedHashMap<Keyy, Anyy>,
+}
+
+impl SdProtected {
+    /// Starts building a protected header, in which both `alg` and `typ` are mandatory
+    pub fn builder() -> SdProtectedBuilder {
+        SdProtectedBuilder {
+            alg: None,
+            typ: None,
+            custom: OrderedHashMap::new(),
+        }
+    }
+}
+
+impl SdProtectedBuilder {
+    /// COSE signing algorithm e.g. -8 for EdDSA
+    pub fn alg(mut self, alg: i64) -> Self {
+        self.alg = Some(alg);
+        self
+    }
+
+    pub fn typ(mut self, typ: impl Into<String>) -> Self {
+        self.typ = Some(typ.into());
+        self
+    }
+
+    /// Adds any other header, by label or by name
+    pub fn header(mut self, key: impl Into<Keyy>, value: impl Into<Anyy>) -> Self {
+        self.custom.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SdProtected, BuilderError> {
+        if let Some(label) = reserved(&self.custom, &PROTECTED_LABELS) {
+            return Err(BuilderError::ReservedLabel(label));
+        }
+        let alg = self.alg.ok_or(BuilderError::MissingField(ALG_LABEL))?;
+        let typ = self.typ.ok_or(BuilderError::MissingField(TYP_LABEL))?;
+        Ok(SdProtected::new(alg.into(), typ, self.custom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::{Deserialize, ToCBORBytes};
+
+    #[test]
+    fn should_build_payload() {
+        let payload = SdPayload::builder()
+            .issuer("https://issuer.example")
+            .audience("https://verifier.example")
+            .issued_at(1725244200)
+            .expires_in(Duration::from_secs(3600))
+            .not_before(UNIX_EPOCH + Duration::from_secs(1725240000))
+            .sd_alg(-16)
+            .claim(500, serde_json::json!({ "name": "John" }))
+            .claim("email", serde_json::json!("johndoe@example.com"))
+            .build()
+            .unwrap();
+        assert_eq!(payload.iss.as_deref(), Some("https://issuer.example"));
+        assert_eq!(payload.iat, Int::new_uint(1725244200));
+        assert_eq!(payload.exp, Some(Int::new_uint(1725247800)));
+        assert_eq!(payload.nbf, Some(Int::new_uint(1725240000)));
+        assert_eq!(payload.sd_alg, Some(Int::new_nint(15)));
+        assert_eq!(payload.custom.len(), 2);
+
+        // encodes as a regular payload
+        let decoded = SdPayload::from_cbor_bytes(&payload.to_cbor_bytes()).unwrap();
+        assert_eq!(decoded.aud, "https://verifier.example");
+        assert_eq!(
+            decoded.custom.get(&Keyy::from(500)),
+            payload.custom.get(&Keyy::from(500))
+        );
+    }
+
+    #[test]
+    fn should_default_iat_to_now() {
+        let before = NumericDate::now();
+        let payload = SdPayload::builder().audience("aud").build().unwrap();
+        assert!(payload.iat >= Int::from(before));
+    }
+
+    #[test]
+    fn should_fail_without_mandatory_fields() {
+        assert_eq!(
+            SdPayload::builder().issuer("iss").build().unwrap_err(),
+            BuilderError::MissingField(3)
+        );
+        assert_eq!(
+            SdPayload::builder()
+                .audience("aud")
+                .claim(4, serde_json::json!(0))
+                .build()
+                .unwrap_err(),
+            BuilderError::ReservedLabel(4)
+        );
+        assert_eq!(
+            SdProtected::builder().alg(-8).build().unwrap_err(),
+            BuilderError::MissingField(16)
+        );
+
+        let protected = SdProtected::builder()
+            .alg(-8)
+            .typ("application/sd+cwt")
+            .header(4, serde_json::json!("kid"))
+            .build()
+            .unwrap();
+        assert_eq!(protected.alg, Int::new_nint(7));
+        assert_eq!(protected.custom.len(), 1);
+    }
+}
@@ -4,12 +4,6 @@
 use crate::serialization::{LenEncoding, StringEncoding};
 use std::collections::BTreeMap;
 
-#[derive(Clone, Debug, Default)]
-pub struct AnyyEncoding {
-    pub len_encoding: LenEncoding,
-    pub index_0_encoding: Option<cbor_event::Sz>,
-}
-
 #[derive(Clone, Debug, Default)]
 pub struct SaltedClaimItemEncoding {
     pub len_encoding: LenEncoding,
@@ -54,8 +48,6 @@ pub struct SdPayloadEncoding {
     pub redacted_keys_encoding: LenEncoding,
     pub redacted_keys_elem_encodings: Vec<StringEncoding>,
     pub redacted_keys_key_encoding: Option<cbor_event::Sz>,
-    pub custom_encoding: LenEncoding,
-    pub custom_key_encoding: StringEncoding,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -65,8 +57,6 @@ pub struct SdProtectedEncoding {
     pub alg_key_encoding: Option<cbor_event::Sz>,
     pub typ_encoding: StringEncoding,
     pub typ_key_encoding: Option<cbor_event::Sz>,
-    pub custom_encoding: LenEncoding,
-    pub custom_key_encoding: StringEncoding,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -77,6 +67,4 @@ pub struct UnprotectedEncoding {
     pub sd_claims_key_encoding: Option<cbor_event::Sz>,
     pub sd_kbt_encoding: StringEncoding,
     pub sd_kbt_key_encoding: Option<cbor_event::Sz>,
-    pub custom_encoding: LenEncoding,
-    pub custom_key_encoding: StringEncoding,
 }
@@ -1,5 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 
+pub mod anyy;
+pub mod builder;
 pub mod error;
 pub mod ordered_hash_map;
 extern crate derivative;
@@ -9,33 +11,18 @@ extern crate derivative;
 pub mod cbor_encodings;
 pub mod serialization;
 
+pub use crate::anyy::Anyy;
+pub use crate::builder::{BuilderError, NumericDate, SdPayloadBuilder, SdProtectedBuilder};
 use crate::error::*;
 use crate::ordered_hash_map::OrderedHashMap;
 use crate::serialization::{LenEncoding, StringEncoding};
 use cbor_encodings::{
-    AnyyEncoding, SaltedClaimItemEncoding, SaltedElementItemEncoding, SdCwtEncoding,
-    SdPayloadEncoding, SdProtectedEncoding, UnprotectedEncoding,
+    SaltedClaimItemEncoding, SaltedElementItemEncoding, SdCwtEncoding, SdPayloadEncoding,
+    SdProtectedEncoding, UnprotectedEncoding,
 };
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
-#[derive(Clone, Debug)]
-pub struct Anyy {
-    pub encodings: Option<AnyyEncoding>,
-}
-
-impl Anyy {
-    pub fn new() -> Self {
-        Self { encodings: None }
-    }
-}
-
-impl Default for Anyy {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 #[derive(Clone, Debug, derivative::Derivative)]
 #[derivative(
     Eq,
@@ -293,6 +280,8 @@ impl SdCwt {
     }
 }
 
+pub type SdCwtKbt = SdCwt;
+
 #[derive(Clone, Debug)]
 pub struct SdPayload {
     pub iss: Option<String>,
@@ -18,6 +18,30 @@ impl<T: cbor_event::de::Deserialize> Deserialize for T {
         T::deserialize(raw).map_err(DeserializeError::from)
     }
 }
+
+/// Reads the value of a claim inlined in a map although it is not one of its fields
+fn deserialize_custom_claim<R: BufRead + Seek>(
+    raw: &mut Deserializer<R>,
+    read_len: &mut CBORReadLen,
+    custom: &mut OrderedHashMap<Keyy, Anyy>,
+    key: Keyy,
+) -> Result<(), DeserializeError> {
+    read_len.read_elems(1)?;
+    let (location, duplicate_key) = match &key {
+        Keyy::Int(Int::Uint { value, .. }) => (value.to_string(), Key::Uint(*value)),
+        Keyy::Int(int @ Int::Nint { value, .. }) => {
+            (int.to_string(), Key::Nint(-1 - i128::from(*value)))
+        }
+        Keyy::Text { text, .. } => (text.clone(), Key::Str(text.clone())),
+    };
+    if custom.contains_key(&key) {
+        return Err(DeserializeFailure::DuplicateKey(duplicate_key).into());
+    }
+    let value = Anyy::deserialize(raw).map_err(|e| e.annotate(location))?;
+    custom.insert(key, value);
+    Ok(())
+}
+
 pub struct CBORReadLen {
     deser_len: cbor_event::LenSz,
     read: u64,
@@ -219,74 +243,6 @@ use cbor_event::de::Deserializer;
 use cbor_event::se::{Serialize, Serializer};
 use std::io::{BufRead, Seek, SeekFrom, Write};
 
-impl cbor_event::se::Serialize for Anyy {
-    fn serialize<'se, W: Write>(
-        &self,
-        serializer: &'se mut Serializer<W>,
-    ) -> cbor_event::Result<&'se mut Serializer<W>> {
-        serializer.write_array_sz(
-            self.encodings
-                .as_ref()
-                .map(|encs| encs.len_encoding)
-                .unwrap_or_default()
-                .to_len_sz(1),
-        )?;
-        serializer.write_unsigned_integer_sz(
-            0u64,
-            fit_sz(
-                0u64,
-                self.encodings
-                    .as_ref()
-                    .map(|encs| encs.index_0_encoding)
-                    .unwrap_or_default(),
-            ),
-        )?;
-        self.encodings
-            .as_ref()
-            .map(|encs| encs.len_encoding)
-            .unwrap_or_default()
-            .end(serializer)
-    }
-}
-
-impl Deserialize for Anyy {
-    fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
-        let len = raw.array_sz()?;
-        let len_encoding: LenEncoding = len.into();
-        let mut read_len = CBORReadLen::new(len);
-        read_len.read_elems(1)?;
-        read_len.finish()?;
-        (|| -> Result<_, DeserializeError> {
-            let index_0_encoding = (|| -> Result<_, DeserializeError> {
-                let (index_0_value, index_0_encoding) = raw.unsigned_integer_sz()?;
-                if index_0_value != 0 {
-                    return Err(DeserializeFailure::FixedValueMismatch {
-                        found: Key::Uint(index_0_value),
-                        expected: Key::Uint(0),
-                    }
-                    .into());
-                }
-                Ok(Some(index_0_encoding))
-            })()
-            .map_err(|e| e.annotate("index_0"))?;
-            match len {
-                cbor_event::LenSz::Len(_, _) => (),
-                cbor_event::LenSz::Indefinite => match raw.special()? {
-                    cbor_event::Special::Break => (),
-                    _ => return Err(DeserializeFailure::EndingBreakMissing.into()),
-                },
-            }
-            Ok(Anyy {
-                encodings: Some(AnyyEncoding {
-                    len_encoding,
-                    index_0_encoding,
-                }),
-            })
-        })()
-        .map_err(|e| e.annotate("Anyy"))
-    }
-}
-
 impl cbor_event::se::Serialize for Int {
     fn serialize<'se, W: Write>(
         &self,
@@ -346,7 +302,7 @@ impl Deserialize for IntOrText {
     fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
         (|| -> Result<_, DeserializeError> {
             match raw.cbor_type()? {
-                cbor_event::Type::Array | cbor_event::Type::Map => {
+                cbor_event::Type::UnsignedInteger | cbor_event::Type::NegativeInteger => {
                     Ok(IntOrText::Int(Int::deserialize(raw)?))
                 }
                 cbor_event::Type::Text => {
@@ -387,7 +343,7 @@ impl Deserialize for Keyy {
     fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
         (|| -> Result<_, DeserializeError> {
             match raw.cbor_type()? {
-                cbor_event::Type::Array | cbor_event::Type::Map => {
+                cbor_event::Type::UnsignedInteger | cbor_event::Type::NegativeInteger => {
                     Ok(Keyy::Int(Int::deserialize(raw)?))
                 }
                 cbor_event::Type::Text => {
@@ -797,7 +753,7 @@ impl cbor_event::se::Serialize for SdPayload {
                 .map(|encs| encs.len_encoding)
                 .unwrap_or_default()
                 .to_len_sz(
-                    3 + match &self.iss {
+                    2 + match &self.iss {
                         Some(_) => 1,
                         None => 0,
                     } + match &self.sub {
@@ -824,7 +780,7 @@ impl cbor_event::se::Serialize for SdPayload {
                     } + match &self.redacted_keys {
                         Some(_) => 1,
                         None => 0,
-                    },
+                    } + self.custom.len() as u64,
                 ),
         )?;
         let deser_order = self
@@ -832,7 +788,7 @@ impl cbor_event::se::Serialize for SdPayload {
             .as_ref()
             .filter(|encs| {
                 encs.orig_deser_order.len()
-                    == 3 + match &self.iss {
+                    == 2 + match &self.iss {
                         Some(_) => 1,
                         None => 0,
                     } + match &self.sub {
@@ -859,10 +815,16 @@ impl cbor_event::se::Serialize for SdPayload {
                     } + match &self.redacted_keys {
                         Some(_) => 1,
                         None => 0,
-                    }
+                    } + self.custom.len()
             })
             .map(|encs| encs.orig_deser_order.clone())
-            .unwrap_or_else(|| (0..12).collect());
+            .unwrap_or_else(|| {
+                (0..11)
+                    .chain(std::iter::repeat_n(11, self.custom.len()))
+                    .collect()
+            });
+        // custom claims are inlined in the map, in their original order
+        let mut custom = self.custom.iter();
         for field_index in deser_order {
             match field_index {
                 0 => {
@@ -1100,34 +1062,19 @@ impl cbor_event::se::Serialize for SdPayload {
                     }
                 }
                 11 => {
-                    serializer.write_text_sz(
-                        "custom",
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_key_encoding.clone())
-                            .unwrap_or_default()
-                            .to_str_len_sz("custom".len() as u64),
-                    )?;
-                    serializer.write_map_sz(
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_encoding)
-                            .unwrap_or_default()
-                            .to_len_sz(self.custom.len() as u64),
-                    )?;
-                    for (key, value) in self.custom.iter() {
+                    if let Some((key, value)) = custom.next() {
                         key.serialize(serializer)?;
                         value.serialize(serializer)?;
                     }
-                    self.encodings
-                        .as_ref()
-                        .map(|encs| encs.custom_encoding)
-                        .unwrap_or_default()
-                        .end(serializer)?;
                 }
                 _ => unreachable!(),
             };
         }
+        // custom claims added since deserialization
+        for (key, value) in custom {
+            key.serialize(serializer)?;
+            value.serialize(serializer)?;
+        }
         self.encodings
             .as_ref()
             .map(|encs| encs.len_encoding)
@@ -1141,7 +1088,7 @@ impl Deserialize for SdPayload {
         let len = raw.map_sz()?;
         let len_encoding: LenEncoding = len.into();
         let mut read_len = CBORReadLen::new(len);
-        read_len.read_elems(3)?;
+        read_len.read_elems(2)?;
         (|| -> Result<_, DeserializeError> {
             let mut orig_deser_order = Vec::new();
             let mut iss_encoding = StringEncoding::default();
@@ -1174,9 +1121,7 @@ impl Deserialize for SdPayload {
             let mut redacted_keys_elem_encodings = Vec::new();
             let mut redacted_keys_key_encoding = None;
             let mut redacted_keys = None;
-            let mut custom_encoding = LenEncoding::default();
-            let mut custom_key_encoding = StringEncoding::default();
-            let mut custom = None;
+            let mut custom = OrderedHashMap::new();
             let mut read = 0;
             while match len {
                 cbor_event::LenSz::Len(n, _) => read < n,
@@ -1403,69 +1348,28 @@ impl Deserialize for SdPayload {
                             redacted_keys_key_encoding = Some(key_enc);
                             orig_deser_order.push(10);
                         }
-                        (unknown_key, _enc) => {
-                            return Err(
-                                DeserializeFailure::UnknownKey(Key::Uint(unknown_key)).into()
-                            )
+                        (unknown_key, key_enc) => {
+                            let key = Keyy::new_int(Int::Uint {
+                                value: unknown_key,
+                                encoding: Some(key_enc),
+                            });
+                            deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                            orig_deser_order.push(11);
                         }
                     },
+                    cbor_event::Type::NegativeInteger => {
+                        let key = Keyy::new_int(Int::deserialize(raw)?);
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(11);
+                    }
                     cbor_event::Type::Text => {
-                        let (text_key, key_enc) = raw.text_sz()?;
-                        match text_key.as_str() {
-                            "custom" => {
-                                if custom.is_some() {
-                                    return Err(DeserializeFailure::DuplicateKey(Key::Str(
-                                        "custom".into(),
-                                    ))
-                                    .into());
-                                }
-                                let (tmp_custom, tmp_custom_encoding) =
-                                    (|| -> Result<_, DeserializeError> {
-                                        let mut custom_table = OrderedHashMap::new();
-                                        let custom_len = raw.map_sz()?;
-                                        let custom_encoding = custom_len.into();
-                                        while match custom_len {
-                                            cbor_event::LenSz::Len(n, _) => {
-                                                (custom_table.len() as u64) < n
-                                            }
-                                            cbor_event::LenSz::Indefinite => true,
-                                        } {
-                                            if raw.cbor_type()? == cbor_event::Type::Special {
-                                                assert_eq!(
-                                                    raw.special()?,
-                                                    cbor_event::Special::Break
-                                                );
-                                                break;
-                                            }
-                                            let custom_key = Keyy::deserialize(raw)?;
-                                            let custom_value = Anyy::deserialize(raw)?;
-                                            if custom_table
-                                                .insert(custom_key.clone(), custom_value)
-                                                .is_some()
-                                            {
-                                                return Err(DeserializeFailure::DuplicateKey(
-                                                    Key::Str(String::from(
-                                                        "some complicated/unsupported type",
-                                                    )),
-                                                )
-                                                .into());
-                                            }
-                                        }
-                                        Ok((custom_table, custom_encoding))
-                                    })()
-                                    .map_err(|e| e.annotate("custom"))?;
-                                custom = Some(tmp_custom);
-                                custom_encoding = tmp_custom_encoding;
-                                custom_key_encoding = StringEncoding::from(key_enc);
-                                orig_deser_order.push(11);
-                            }
-                            unknown_key => {
-                                return Err(DeserializeFailure::UnknownKey(Key::Str(
-                                    unknown_key.to_owned(),
-                                ))
-                                .into())
-                            }
-                        }
+                        let (text, text_encoding) = raw.text_sz()?;
+                        let key = Keyy::Text {
+                            text,
+                            text_encoding: text_encoding.into(),
+                        };
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(11);
                     }
                     cbor_event::Type::Special => match len {
                         cbor_event::LenSz::Len(_, _) => {
@@ -1490,15 +1394,6 @@ impl Deserialize for SdPayload {
                 Some(x) => x,
                 None => return Err(DeserializeFailure::MandatoryFieldMissing(Key::Uint(6)).into()),
             };
-            let custom = match custom {
-                Some(x) => x,
-                None => {
-                    return Err(
-                        DeserializeFailure::MandatoryFieldMissing(Key::Str(String::from("custom")))
-                            .into(),
-                    )
-                }
-            };
             read_len.finish()?;
             Ok(Self {
                 iss,
@@ -1535,8 +1430,6 @@ impl Deserialize for SdPayload {
                     redacted_keys_key_encoding,
                     redacted_keys_encoding,
                     redacted_keys_elem_encodings,
-                    custom_key_encoding,
-                    custom_encoding,
                 }),
             })
         })()
@@ -1554,14 +1447,20 @@ impl cbor_event::se::Serialize for SdProtected {
                 .as_ref()
                 .map(|encs| encs.len_encoding)
                 .unwrap_or_default()
-                .to_len_sz(3),
+                .to_len_sz(2 + self.custom.len() as u64),
         )?;
         let deser_order = self
             .encodings
             .as_ref()
-            .filter(|encs| encs.orig_deser_order.len() == 3)
+            .filter(|encs| encs.orig_deser_order.len() == 2 + self.custom.len())
             .map(|encs| encs.orig_deser_order.clone())
-            .unwrap_or_else(|| (0..3).collect());
+            .unwrap_or_else(|| {
+                (0..2)
+                    .chain(std::iter::repeat_n(2, self.custom.len()))
+                    .collect()
+            });
+        // custom claims are inlined in the map, in their original order
+        let mut custom = self.custom.iter();
         for field_index in deser_order {
             match field_index {
                 0 => {
@@ -1598,34 +1497,19 @@ impl cbor_event::se::Serialize for SdProtected {
                     )?;
                 }
                 2 => {
-                    serializer.write_text_sz(
-                        "custom",
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_key_encoding.clone())
-                            .unwrap_or_default()
-                            .to_str_len_sz("custom".len() as u64),
-                    )?;
-                    serializer.write_map_sz(
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_encoding)
-                            .unwrap_or_default()
-                            .to_len_sz(self.custom.len() as u64),
-                    )?;
-                    for (key, value) in self.custom.iter() {
+                    if let Some((key, value)) = custom.next() {
                         key.serialize(serializer)?;
                         value.serialize(serializer)?;
                     }
-                    self.encodings
-                        .as_ref()
-                        .map(|encs| encs.custom_encoding)
-                        .unwrap_or_default()
-                        .end(serializer)?;
                 }
                 _ => unreachable!(),
             };
         }
+        // custom claims added since deserialization
+        for (key, value) in custom {
+            key.serialize(serializer)?;
+            value.serialize(serializer)?;
+        }
         self.encodings
             .as_ref()
             .map(|encs| encs.len_encoding)
@@ -1639,8 +1523,7 @@ impl Deserialize for SdProtected {
         let len = raw.map_sz()?;
         let len_encoding: LenEncoding = len.into();
         let mut read_len = CBORReadLen::new(len);
-        read_len.read_elems(3)?;
-        read_len.finish()?;
+        read_len.read_elems(2)?;
         (|| -> Result<_, DeserializeError> {
             let mut orig_deser_order = Vec::new();
             let mut alg_key_encoding = None;
@@ -1648,9 +1531,7 @@ impl Deserialize for SdProtected {
             let mut typ_encoding = StringEncoding::default();
             let mut typ_key_encoding = None;
             let mut typ = None;
-            let mut custom_encoding = LenEncoding::default();
-            let mut custom_key_encoding = StringEncoding::default();
-            let mut custom = None;
+            let mut custom = OrderedHashMap::new();
             let mut read = 0;
             while match len {
                 cbor_event::LenSz::Len(n, _) => read < n,
@@ -1682,69 +1563,28 @@ impl Deserialize for SdProtected {
                             typ_key_encoding = Some(key_enc);
                             orig_deser_order.push(1);
                         }
-                        (unknown_key, _enc) => {
-                            return Err(
-                                DeserializeFailure::UnknownKey(Key::Uint(unknown_key)).into()
-                            )
+                        (unknown_key, key_enc) => {
+                            let key = Keyy::new_int(Int::Uint {
+                                value: unknown_key,
+                                encoding: Some(key_enc),
+                            });
+                            deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                            orig_deser_order.push(2);
                         }
                     },
+                    cbor_event::Type::NegativeInteger => {
+                        let key = Keyy::new_int(Int::deserialize(raw)?);
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(2);
+                    }
                     cbor_event::Type::Text => {
-                        let (text_key, key_enc) = raw.text_sz()?;
-                        match text_key.as_str() {
-                            "custom" => {
-                                if custom.is_some() {
-                                    return Err(DeserializeFailure::DuplicateKey(Key::Str(
-                                        "custom".into(),
-                                    ))
-                                    .into());
-                                }
-                                let (tmp_custom, tmp_custom_encoding) =
-                                    (|| -> Result<_, DeserializeError> {
-                                        let mut custom_table = OrderedHashMap::new();
-                                        let custom_len = raw.map_sz()?;
-                                        let custom_encoding = custom_len.into();
-                                        while match custom_len {
-                                            cbor_event::LenSz::Len(n, _) => {
-                                                (custom_table.len() as u64) < n
-                                            }
-                                            cbor_event::LenSz::Indefinite => true,
-                                        } {
-                                            if raw.cbor_type()? == cbor_event::Type::Special {
-                                                assert_eq!(
-                                                    raw.special()?,
-                                                    cbor_event::Special::Break
-                                                );
-                                                break;
-                                            }
-                                            let custom_key = Keyy::deserialize(raw)?;
-                                            let custom_value = Anyy::deserialize(raw)?;
-                                            if custom_table
-                                                .insert(custom_key.clone(), custom_value)
-                                                .is_some()
-                                            {
-                                                return Err(DeserializeFailure::DuplicateKey(
-                                                    Key::Str(String::from(
-                                                        "some complicated/unsupported type",
-                                                    )),
-                                                )
-                                                .into());
-                                            }
-                                        }
-                                        Ok((custom_table, custom_encoding))
-                                    })()
-                                    .map_err(|e| e.annotate("custom"))?;
-                                custom = Some(tmp_custom);
-                                custom_encoding = tmp_custom_encoding;
-                                custom_key_encoding = StringEncoding::from(key_enc);
-                                orig_deser_order.push(2);
-                            }
-                            unknown_key => {
-                                return Err(DeserializeFailure::UnknownKey(Key::Str(
-                                    unknown_key.to_owned(),
-                                ))
-                                .into())
-                            }
-                        }
+                        let (text, text_encoding) = raw.text_sz()?;
+                        let key = Keyy::Text {
+                            text,
+                            text_encoding: text_encoding.into(),
+                        };
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(2);
                     }
                     cbor_event::Type::Special => match len {
                         cbor_event::LenSz::Len(_, _) => {
@@ -1769,16 +1609,8 @@ impl Deserialize for SdProtected {
                 Some(x) => x,
                 None => return Err(DeserializeFailure::MandatoryFieldMissing(Key::Uint(16)).into()),
             };
-            let custom = match custom {
-                Some(x) => x,
-                None => {
-                    return Err(
-                        DeserializeFailure::MandatoryFieldMissing(Key::Str(String::from("custom")))
-                            .into(),
-                    )
-                }
-            };
             ();
+            read_len.finish()?;
             Ok(Self {
                 alg,
                 typ,
@@ -1789,8 +1621,6 @@ impl Deserialize for SdProtected {
                     alg_key_encoding,
                     typ_key_encoding,
                     typ_encoding,
-                    custom_key_encoding,
-                    custom_encoding,
                 }),
             })
         })()
@@ -1809,13 +1639,13 @@ impl cbor_event::se::Serialize for Unprotected {
                 .map(|encs| encs.len_encoding)
                 .unwrap_or_default()
                 .to_len_sz(
-                    1 + match &self.sd_claims {
+                    0 + match &self.sd_claims {
                         Some(_) => 1,
                         None => 0,
                     } + match &self.sd_kbt {
                         Some(_) => 1,
                         None => 0,
-                    },
+                    } + self.custom.len() as u64,
                 ),
         )?;
         let deser_order = self
@@ -1823,16 +1653,22 @@ impl cbor_event::se::Serialize for Unprotected {
             .as_ref()
             .filter(|encs| {
                 encs.orig_deser_order.len()
-                    == 1 + match &self.sd_claims {
+                    == 0 + match &self.sd_claims {
                         Some(_) => 1,
                         None => 0,
                     } + match &self.sd_kbt {
                         Some(_) => 1,
                         None => 0,
-                    }
+                    } + self.custom.len()
             })
             .map(|encs| encs.orig_deser_order.clone())
-            .unwrap_or_else(|| (0..3).collect());
+            .unwrap_or_else(|| {
+                (0..2)
+                    .chain(std::iter::repeat_n(2, self.custom.len()))
+                    .collect()
+            });
+        // custom claims are inlined in the map, in their original order
+        let mut custom = self.custom.iter();
         for field_index in deser_order {
             match field_index {
                 0 => {
@@ -1887,34 +1723,19 @@ impl cbor_event::se::Serialize for Unprotected {
                     }
                 }
                 2 => {
-                    serializer.write_text_sz(
-                        "custom",
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_key_encoding.clone())
-                            .unwrap_or_default()
-                            .to_str_len_sz("custom".len() as u64),
-                    )?;
-                    serializer.write_map_sz(
-                        self.encodings
-                            .as_ref()
-                            .map(|encs| encs.custom_encoding)
-                            .unwrap_or_default()
-                            .to_len_sz(self.custom.len() as u64),
-                    )?;
-                    for (key, value) in self.custom.iter() {
+                    if let Some((key, value)) = custom.next() {
                         key.serialize(serializer)?;
                         value.serialize(serializer)?;
                     }
-                    self.encodings
-                        .as_ref()
-                        .map(|encs| encs.custom_encoding)
-                        .unwrap_or_default()
-                        .end(serializer)?;
                 }
                 _ => unreachable!(),
             };
         }
+        // custom claims added since deserialization
+        for (key, value) in custom {
+            key.serialize(serializer)?;
+            value.serialize(serializer)?;
+        }
         self.encodings
             .as_ref()
             .map(|encs| encs.len_encoding)
@@ -1928,7 +1749,6 @@ impl Deserialize for Unprotected {
         let len = raw.map_sz()?;
         let len_encoding: LenEncoding = len.into();
         let mut read_len = CBORReadLen::new(len);
-        read_len.read_elems(1)?;
         (|| -> Result<_, DeserializeError> {
             let mut orig_deser_order = Vec::new();
             let mut sd_claims_encoding = LenEncoding::default();
@@ -1937,9 +1757,7 @@ impl Deserialize for Unprotected {
             let mut sd_kbt_encoding = StringEncoding::default();
             let mut sd_kbt_key_encoding = None;
             let mut sd_kbt = None;
-            let mut custom_encoding = LenEncoding::default();
-            let mut custom_key_encoding = StringEncoding::default();
-            let mut custom = None;
+            let mut custom = OrderedHashMap::new();
             let mut read = 0;
             while match len {
                 cbor_event::LenSz::Len(n, _) => read < n,
@@ -1998,69 +1816,28 @@ impl Deserialize for Unprotected {
                             sd_kbt_key_encoding = Some(key_enc);
                             orig_deser_order.push(1);
                         }
-                        (unknown_key, _enc) => {
-                            return Err(
-                                DeserializeFailure::UnknownKey(Key::Uint(unknown_key)).into()
-                            )
+                        (unknown_key, key_enc) => {
+                            let key = Keyy::new_int(Int::Uint {
+                                value: unknown_key,
+                                encoding: Some(key_enc),
+                            });
+                            deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                            orig_deser_order.push(2);
                         }
                     },
+                    cbor_event::Type::NegativeInteger => {
+                        let key = Keyy::new_int(Int::deserialize(raw)?);
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(2);
+                    }
                     cbor_event::Type::Text => {
-                        let (text_key, key_enc) = raw.text_sz()?;
-                        match text_key.as_str() {
-                            "custom" => {
-                                if custom.is_some() {
-                                    return Err(DeserializeFailure::DuplicateKey(Key::Str(
-                                        "custom".into(),
-                                    ))
-                                    .into());
-                                }
-                                let (tmp_custom, tmp_custom_encoding) =
-                                    (|| -> Result<_, DeserializeError> {
-                                        let mut custom_table = OrderedHashMap::new();
-                                        let custom_len = raw.map_sz()?;
-                                        let custom_encoding = custom_len.into();
-                                        while match custom_len {
-                                            cbor_event::LenSz::Len(n, _) => {
-                                                (custom_table.len() as u64) < n
-                                            }
-                                            cbor_event::LenSz::Indefinite => true,
-                                        } {
-                                            if raw.cbor_type()? == cbor_event::Type::Special {
-                                                assert_eq!(
-                                                    raw.special()?,
-                                                    cbor_event::Special::Break
-                                                );
-                                                break;
-                                            }
-                                            let custom_key = Keyy::deserialize(raw)?;
-                                            let custom_value = Anyy::deserialize(raw)?;
-                                            if custom_table
-                                                .insert(custom_key.clone(), custom_value)
-                                                .is_some()
-                                            {
-                                                return Err(DeserializeFailure::DuplicateKey(
-                                                    Key::Str(String::from(
-                                                        "some complicated/unsupported type",
-                                                    )),
-                                                )
-                                                .into());
-                                            }
-                                        }
-                                        Ok((custom_table, custom_encoding))
-                                    })()
-                                    .map_err(|e| e.annotate("custom"))?;
-                                custom = Some(tmp_custom);
-                                custom_encoding = tmp_custom_encoding;
-                                custom_key_encoding = StringEncoding::from(key_enc);
-                                orig_deser_order.push(2);
-                            }
-                            unknown_key => {
-                                return Err(DeserializeFailure::UnknownKey(Key::Str(
-                                    unknown_key.to_owned(),
-                                ))
-                                .into())
-                            }
-                        }
+                        let (text, text_encoding) = raw.text_sz()?;
+                        let key = Keyy::Text {
+                            text,
+                            text_encoding: text_encoding.into(),
+                        };
+                        deserialize_custom_claim(raw, &mut read_len, &mut custom, key)?;
+                        orig_deser_order.push(2);
                     }
                     cbor_event::Type::Special => match len {
                         cbor_event::LenSz::Len(_, _) => {
@@ -2077,15 +1854,6 @@ impl Deserialize for Unprotected {
                 }
                 read += 1;
             }
-            let custom = match custom {
-                Some(x) => x,
-                None => {
-                    return Err(
-                        DeserializeFailure::MandatoryFieldMissing(Key::Str(String::from("custom")))
-                            .into(),
-                    )
-                }
-            };
             read_len.finish()?;
             Ok(Self {
                 sd_claims,
@@ -2098,8 +1866,6 @@ impl Deserialize for Unprotected {
                     sd_claims_encoding,
                     sd_kbt_key_encoding,
                     sd_kbt_encoding,
-                    custom_key_encoding,
-                    custom_encoding,
                 }),
             })
         })()
@@ -0,0 +1,248 @@
+use ciborium::Value as CborValue;
+use sd_cwt_types::Int;
+use serde_yaml::Value as YamlValue;
+
+use crate::error::{SdCwtError, SdCwtResult};
+
+/// Converts a YAML claim tree into its CBOR counterpart, stripping every YAML tag on the way
+pub(crate) fn yaml_to_cbor(value: &YamlValue) -> SdCwtResult<CborValue> {
+    Ok(match value {
+        YamlValue::Null => CborValue::Null,
+        YamlValue::Bool(b) => CborValue::Bool(*b),
+        YamlValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                CborValue::Integer(i.into())
+            } else if let Some(u) = n.as_u64() {
+                CborValue::Integer(u.into())
+            } else {
+                let f = n
+                    .as_f64()
+                    .ok_or_else(|| SdCwtError::CborError(format!("unsupported number {n}")))?;
+                CborValue::Float(f)
+            }
+        }
+        YamlValue::String(s) => CborValue::Text(s.clone()),
+        YamlValue::Sequence(seq) => {
+            CborValue::Array(seq.iter().map(yaml_to_cbor).collect::<SdCwtResult<_>>()?)
+        }
+        YamlValue::Mapping(map) => CborValue::Map(
+            map.iter()
+                .map(|(k, v)| Ok((yaml_to_cbor(k)?, yaml_to_cbor(v)?)))
+                .collect::<SdCwtResult<_>>()?,
+        ),
+        YamlValue::Tagged(tagged) => yaml_to_cbor(&tagged.value)?,
+    })
+}
+
+/// Builds the COSE `Sig_structure` for a COSE_Sign1 without external AAD
+pub(crate) fn sig_structure(protected: &[u8], payload: &[u8]) -> SdCwtResult<Vec<u8>> {
+    let sig_structure = CborValue::Array(vec![
+        CborValue::Text("Signature1".to_string()),
+        CborValue::Bytes(protected.to_vec()),
+        CborValue::Bytes(vec![]),
+        CborValue::Bytes(payload.to_vec()),
+    ]);
+    to_vec(&sig_structure)
+}
+
+pub(crate) trait IntoInt {
+    fn into_int(self) -> Int;
+}
+
+impl IntoInt for i64 {
+    fn into_int(self) -> Int {
+        // an i64 always fits in a CBOR int
+        Int::try_from(self as i128).unwrap()
+    }
+}
+
+/// Reads a CBOR int as an i64, `None` when it does not fit
+pub(crate) fn int_to_i64(int: &Int) -> Option<i64> {
+    match int {
+        Int::Uint { value, .. } => i64::try_from(*value).ok(),
+        Int::Nint { value, .. } => i64::try_from(*value).ok().map(|v| -v - 1),
+    }
+}
+
+pub(crate) fn from_slice(bytes: &[u8]) -> SdCwtResult<CborValue> {
+    ciborium::from_reader(bytes).map_err(|e| SdCwtError::CborError(e.to_string()))
+}
+
+pub(crate) fn to_vec(value: &CborValue) -> SdCwtResult<Vec<u8>> {
+    let mut buf = vec![];
+    ciborium::into_writer(value, &mut buf).map_err(|e| SdCwtError::CborError(e.to_string()))?;
+    Ok(buf)
+}
+
+/// Grants access to the unprotected header of a tagged COSE_Sign1
+pub(crate) fn unprotected_mut(
+    cose_sign1: &mut CborValue,
+) -> SdCwtResult<&mut Vec<(CborValue, CborValue)>> {
+    match cose_sign1 {
+        CborValue::Tag(_, cose_sign1) => match cose_sign1.as_mut() {
+            CborValue::Array(items) => items.get_mut(1).and_then(|u| u.as_map_mut()),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| SdCwtError::CborError("expected a tagged COSE_Sign1".into()))
+}
+
+/// Grants read access to the unprotected header of a tagged COSE_Sign1
+pub(crate) fn unprotected(cose_sign1: &CborValue) -> SdCwtResult<&Vec<(CborValue, CborValue)>> {
+    match cose_sign1 {
+        CborValue::Tag(_, cose_sign1) => match cose_sign1.as_ref() {
+            CborValue::Array(items) => items.get(1).and_then(|u| u.as_map()),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| SdCwtError::CborError("expected a tagged COSE_Sign1".into()))
+}
+
+/// Rewrites a tagged COSE_Sign1 without the `label` entry of its unprotected header, then with
+/// `label` set to `value` when given. Every other byte, including the encoding and the order of
+/// the other header entries, is kept as received; only the entry count is encoded anew.
+pub(crate) fn replace_unprotected(
+    cose_sign1: &[u8],
+    label: i64,
+    value: Option<&CborValue>,
+) -> SdCwtResult<Vec<u8>> {
+    let invalid = || SdCwtError::CborError("expected a tagged COSE_Sign1".into());
+    let (major, _, tag_len) = head(cose_sign1)?;
+    if major != TAG {
+        return Err(invalid());
+    }
+    let (major, items, array_len) = head(&cose_sign1[tag_len..])?;
+    if major != ARRAY || items != Some(4) {
+        return Err(invalid());
+    }
+    let protected_start = tag_len + array_len;
+    let map_start = protected_start + item_len(&cose_sign1[protected_start..])?;
+    let (major, len, map_len) = head(&cose_sign1[map_start..])?;
+    if major != MAP {
+        return Err(invalid());
+    }
+
+    let label = CborValue::from(label);
+    let (mut entries, mut count) = (vec![], 0);
+    let mut offset = map_start + map_len;
+    for i in 0.. {
+        match len {
+            Some(len) if i == len => break,
+            None if cose_sign1.get(offset) == Some(&BREAK) => {
+                offset += 1;
+                break;
+            }
+            _ => {}
+        }
+        let key_len = item_len(&cose_sign1[offset..])?;
+        let entry_len = key_len + item_len(&cose_sign1[offset + key_len..])?;
+        if from_slice(&cose_sign1[offset..offset + key_len])? != label {
+            entries.extend_from_slice(&cose_sign1[offset..offset + entry_len]);
+            count += 1;
+        }
+        offset += entry_len;
+    }
+    if let Some(value) = value {
+        entries.extend(to_vec(&label)?);
+        entries.extend(to_vec(value)?);
+        count += 1;
+    }
+
+    let mut bytes = cose_sign1[..map_start].to_vec();
+    match len {
+        Some(_) => push_head(&mut bytes, MAP, count),
+        None => {
+            bytes.push(MAP << 5 | INDEFINITE);
+            entries.push(BREAK);
+        }
+    }
+    bytes.extend(entries);
+    bytes.extend_from_slice(&cose_sign1[offset..]);
+    Ok(bytes)
+}
+
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+/// Major type, argument and length of the head of the first data item of `bytes`, the argument
+/// being `None` for an indefinite length
+fn head(bytes: &[u8]) -> SdCwtResult<(u8, Option<u64>, usize)> {
+    let initial = *bytes.first().ok_or_else(truncated)?;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    match info {
+        0..=23 => Ok((major, Some(u64::from(info)), 1)),
+        24..=27 => {
+            let len = 1 << (info - 24);
+            let argument = bytes
+                .get(1..=len)
+                .ok_or_else(truncated)?
+                .iter()
+                .fold(0, |argument, b| argument << 8 | u64::from(*b));
+            Ok((major, Some(argument), 1 + len))
+        }
+        INDEFINITE if (2..=5).contains(&major) => Ok((major, None, 1)),
+        _ => Err(SdCwtError::CborError(format!(
+            "unexpected byte {initial:#04x}"
+        ))),
+    }
+}
+
+/// Length of the first data item of `bytes`, which must have been decoded already so that its
+/// nesting depth is bounded
+fn item_len(bytes: &[u8]) -> SdCwtResult<usize> {
+    let (major, argument, mut len) = head(bytes)?;
+    let rest = |len: usize| bytes.get(len..).ok_or_else(truncated);
+    match (major, argument) {
+        (0 | 1 | 7, Some(_)) => {}
+        (2 | 3, Some(n)) => len += usize::try_from(n).map_err(|_| truncated())?,
+        (ARRAY | MAP | TAG, Some(n)) => {
+            let items = match major {
+                ARRAY => n,
+                MAP => n.saturating_mul(2),
+                _ => 1,
+            };
+            for _ in 0..items {
+                len += item_len(rest(len)?)?;
+            }
+        }
+        (_, None) => {
+            while rest(len)?.first() != Some(&BREAK) {
+                len += item_len(rest(len)?)?;
+            }
+            len += 1;
+        }
+        _ => return Err(truncated()),
+    }
+    rest(len)?;
+    Ok(len)
+}
+
+/// Appends the shortest head of a data item of type `major`
+fn push_head(bytes: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => bytes.push(major | argument as u8),
+        24..=0xff => bytes.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            bytes.push(major | 25);
+            bytes.extend((argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(major | 26);
+            bytes.extend((argument as u32).to_be_bytes());
+        }
+        _ => {
+            bytes.push(major | 27);
+            bytes.extend(argument.to_be_bytes());
+        }
+    }
+}
+
+fn truncated() -> SdCwtError {
+    SdCwtError::CborError("truncated CBOR item".into())
+}
@@ -0,0 +1,410 @@
+use ciborium::Value as CborValue;
+use jwt_simple::prelude::*;
+use sd_cwt_types::{ordered_hash_map::OrderedHashMap, Anyy, Keyy, SdPayload};
+
+use crate::cbor::{int_to_i64, IntoInt};
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::issuer::CNF_LABEL;
+use crate::signing::{SigningAlgorithm, SigningKey};
+use crate::verifier::VerifierPublicKey;
+use crate::HolderPrivateKey;
+
+/// Label of a COSE_Key within the confirmation claim
+pub const CNF_COSE_KEY_LABEL: i64 = 1;
+/// Label of an encrypted COSE_Key within the confirmation claim
+pub const CNF_ENCRYPTED_COSE_KEY_LABEL: i64 = 2;
+/// Label of a key identifier within the confirmation claim
+pub const CNF_KID_LABEL: i64 = 3;
+
+/// COSE_Key common parameters
+const COSE_KEY_KTY: i64 = 1;
+const COSE_KEY_KID: i64 = 2;
+const COSE_KEY_ALG: i64 = 3;
+/// COSE_Key type specific parameters
+const COSE_KEY_CRV: i64 = -1;
+const COSE_KEY_K: i64 = -1;
+const COSE_KEY_X: i64 = -2;
+const COSE_KEY_Y: i64 = -3;
+
+/// COSE key types
+pub const COSE_KTY_OKP: i64 = 1;
+pub const COSE_KTY_EC2: i64 = 2;
+pub const COSE_KTY_SYMMETRIC: i64 = 4;
+
+/// COSE elliptic curves
+pub const COSE_CRV_P256: i64 = 1;
+pub const COSE_CRV_P384: i64 = 2;
+pub const COSE_CRV_P521: i64 = 3;
+pub const COSE_CRV_ED25519: i64 = 6;
+
+/// Key type specific parameters of a [CoseKey]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoseKeyParams {
+    /// Octet key pair e.g. Ed25519
+    Okp {
+        crv: i64,
+        x: Vec<u8>,
+    },
+    /// Elliptic curve key with both coordinates e.g. P-256
+    Ec2 {
+        crv: i64,
+        x: Vec<u8>,
+        y: Vec<u8>,
+    },
+    Symmetric {
+        k: Vec<u8>,
+    },
+}
+
+/// Public or symmetric COSE_Key (RFC 9052), as confirmed in the `cnf` claim. Private key
+/// parameters are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoseKey {
+    pub params: CoseKeyParams,
+    pub kid: Option<Vec<u8>>,
+    pub alg: Option<i64>,
+}
+
+impl CoseKey {
+    pub fn new(params: CoseKeyParams) -> Self {
+        Self {
+            params,
+            kid: None,
+            alg: None,
+        }
+    }
+
+    pub fn with_kid(mut self, kid: impl Into<Vec<u8>>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn with_alg(mut self, alg: i64) -> Self {
+        self.alg = Some(alg);
+        self
+    }
+
+    pub fn kty(&self) -> i64 {
+        match self.params {
+            CoseKeyParams::Okp { .. } => COSE_KTY_OKP,
+            CoseKeyParams::Ec2 { .. } => COSE_KTY_EC2,
+            CoseKeyParams::Symmetric { .. } => COSE_KTY_SYMMETRIC,
+        }
+    }
+
+    pub fn to_cbor(&self) -> CborValue {
+        let mut entries = vec![(COSE_KEY_KTY.into(), self.kty().into())];
+        if let Some(kid) = &self.kid {
+            entries.push((COSE_KEY_KID.into(), CborValue::Bytes(kid.clone())));
+        }
+        if let Some(alg) = self.alg {
+            entries.push((COSE_KEY_ALG.into(), alg.into()));
+        }
+        match &self.params {
+            CoseKeyParams::Okp { crv, x } => {
+                entries.push((COSE_KEY_CRV.into(), (*crv).into()));
+                entries.push((COSE_KEY_X.into(), CborValue::Bytes(x.clone())));
+            }
+            CoseKeyParams::Ec2 { crv, x, y } => {
+                entries.push((COSE_KEY_CRV.into(), (*crv).into()));
+                entries.push((COSE_KEY_X.into(), CborValue::Bytes(x.clone())));
+                entries.push((COSE_KEY_Y.into(), CborValue::Bytes(y.clone())));
+            }
+            CoseKeyParams::Symmetric { k } => {
+                entries.push((COSE_KEY_K.into(), CborValue::Bytes(k.clone())));
+            }
+        }
+        CborValue::Map(entries)
+    }
+
+    /// Decodes an OKP, EC2 or symmetric COSE_Key, ignoring the parameters it does not know
+    pub fn from_cbor(cose_key: &CborValue) -> SdCwtResult<Self> {
+        let invalid = || SdCwtError::InvalidClaim(CNF_LABEL);
+        let entries = cose_key.as_map().ok_or_else(invalid)?;
+        let param = |label: i64| {
+            let label = CborValue::from(label);
+            entries.iter().find(|(k, _)| *k == label).map(|(_, v)| v)
+        };
+        let int_param = |label: i64| {
+            param(label)
+                .map(|v| {
+                    v.as_integer()
+                        .and_then(|i| i64::try_from(i).ok())
+                        .ok_or_else(invalid)
+                })
+                .transpose()
+        };
+        let bytes_param = |label: i64| {
+            param(label)
+                .map(|v| v.as_bytes().cloned().ok_or_else(invalid))
+                .transpose()
+        };
+
+        let params = match int_param(COSE_KEY_KTY)?.ok_or_else(invalid)? {
+            COSE_KTY_OKP => CoseKeyParams::Okp {
+                crv: int_param(COSE_KEY_CRV)?.ok_or_else(invalid)?,
+                x: bytes_param(COSE_KEY_X)?.ok_or_else(invalid)?,
+            },
+            COSE_KTY_EC2 => CoseKeyParams::Ec2 {
+                crv: int_param(COSE_KEY_CRV)?.ok_or_else(invalid)?,
+                x: bytes_param(COSE_KEY_X)?.ok_or_else(invalid)?,
+                // compressed points, where y is the sign bit, are not supported
+                y: bytes_param(COSE_KEY_Y)?.ok_or_else(invalid)?,
+            },
+            COSE_KTY_SYMMETRIC => CoseKeyParams::Symmetric {
+                k: bytes_param(COSE_KEY_K)?.ok_or_else(invalid)?,
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            params,
+            kid: bytes_param(COSE_KEY_KID)?,
+            alg: int_param(COSE_KEY_ALG)?,
+        })
+    }
+}
+
+impl From<&VerifierPublicKey> for CoseKey {
+    fn from(pk: &VerifierPublicKey) -> Self {
+        let alg = pk.algorithm().cose_alg();
+        let (crv, point) = match pk {
+            VerifierPublicKey::EdDsa(pk) => {
+                let params = CoseKeyParams::Okp {
+                    crv: COSE_CRV_ED25519,
+                    x: pk.to_bytes(),
+                };
+                return Self::new(params).with_alg(alg);
+            }
+            VerifierPublicKey::Es256(pk) => {
+                (COSE_CRV_P256, pk.public_key().to_bytes_uncompressed())
+            }
+            VerifierPublicKey::Es384(pk) => {
+                (COSE_CRV_P384, pk.public_key().to_bytes_uncompressed())
+            }
+            VerifierPublicKey::Es512(pk) => (
+                COSE_CRV_P521,
+                pk.to_encoded_point(false).as_bytes().to_vec(),
+            ),
+        };
+        // uncompressed SEC1 points are 0x04 || x || y
+        let (x, y) = point[1..].split_at((point.len() - 1) / 2);
+        let params = CoseKeyParams::Ec2 {
+            crv,
+            x: x.to_vec(),
+            y: y.to_vec(),
+        };
+        Self::new(params).with_alg(alg)
+    }
+}
+
+impl From<&SigningKey> for CoseKey {
+    fn from(key: &SigningKey) -> Self {
+        Self::from(&key.public_key())
+    }
+}
+
+impl From<&HolderPrivateKey> for CoseKey {
+    fn from(key: &HolderPrivateKey) -> Self {
+        Self::from(&key.public_key())
+    }
+}
+
+impl TryFrom<&CoseKey> for VerifierPublicKey {
+    type Error = SdCwtError;
+
+    /// Only Ed25519 OKP keys and P-256, P-384 or P-521 EC2 ones can verify signatures
+    fn try_from(cose_key: &CoseKey) -> SdCwtResult<Self> {
+        let invalid = || SdCwtError::InvalidClaim(CNF_LABEL);
+        let (alg, point) = match &cose_key.params {
+            CoseKeyParams::Okp {
+                crv: COSE_CRV_ED25519,
+                x,
+            } => (SigningAlgorithm::EdDsa, x.clone()),
+            CoseKeyParams::Ec2 { crv, x, y } => {
+                let alg = match *crv {
+                    COSE_CRV_P256 => SigningAlgorithm::Es256,
+                    COSE_CRV_P384 => SigningAlgorithm::Es384,
+                    COSE_CRV_P521 => SigningAlgorithm::Es512,
+                    _ => return Err(invalid()),
+                };
+                // uncompressed SEC1 point
+                (alg, [&[0x04], &x[..], &y[..]].concat())
+            }
+            _ => return Err(invalid()),
+        };
+        if cose_key
+            .alg
+            .is_some_and(|cose_alg| cose_alg != alg.cose_alg())
+        {
+            return Err(invalid());
+        }
+        Self::from_bytes(alg, &point)
+    }
+}
+
+impl TryFrom<CoseKey> for VerifierPublicKey {
+    type Error = SdCwtError;
+
+    fn try_from(cose_key: CoseKey) -> SdCwtResult<Self> {
+        Self::try_from(&cose_key)
+    }
+}
+
+/// Proof-of-possession key confirmed in the `cnf` claim (RFC 8747)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirmation {
+    CoseKey(CoseKey),
+    /// COSE_Encrypt or COSE_Encrypt0 wrapping a COSE_Key, left opaque
+    EncryptedCoseKey(CborValue),
+    Kid(Vec<u8>),
+}
+
+impl Confirmation {
+    /// Confirmation claim of a payload, `None` when it has none
+    pub fn from_payload(payload: &SdPayload) -> SdCwtResult<Option<Self>> {
+        payload.cnf.as_ref().map(Self::from_cnf).transpose()
+    }
+
+    pub fn from_cnf(cnf: &OrderedHashMap<Keyy, Anyy>) -> SdCwtResult<Self> {
+        let entries = cnf
+            .iter()
+            .map(|(k, v)| {
+                let k = match k {
+                    Keyy::Int(i) => int_to_i64(i).map(CborValue::from),
+                    Keyy::Text { text, .. } => Some(CborValue::Text(text.clone())),
+                };
+                Some((k?, CborValue::try_from(v.clone()).ok()?))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SdCwtError::InvalidClaim(CNF_LABEL))?;
+        Self::from_cbor(&CborValue::Map(entries))
+    }
+
+    pub fn to_cnf(&self) -> OrderedHashMap<Keyy, Anyy> {
+        let (label, value) = self.entry();
+        let mut cnf = OrderedHashMap::new();
+        cnf.insert(Keyy::new_int(label.into_int()), Anyy::from(value));
+        cnf
+    }
+
+    pub fn to_cbor(&self) -> CborValue {
+        let (label, value) = self.entry();
+        CborValue::Map(vec![(label.into(), value)])
+    }
+
+    /// Decodes a confirmation claim holding exactly one of a COSE_Key, an encrypted COSE_Key or a
+    /// key identifier
+    pub fn from_cbor(cnf: &CborValue) -> SdCwtResult<Self> {
+        let invalid = || SdCwtError::InvalidClaim(CNF_LABEL);
+        let [(label, value)] = cnf.as_map().map(Vec::as_slice).ok_or_else(invalid)? else {
+            return Err(invalid());
+        };
+        match label.as_integer().and_then(|l| i64::try_from(l).ok()) {
+            Some(CNF_COSE_KEY_LABEL) => CoseKey::from_cbor(value).map(Self::CoseKey),
+            Some(CNF_ENCRYPTED_COSE_KEY_LABEL) => Ok(Self::EncryptedCoseKey(value.clone())),
+            Some(CNF_KID_LABEL) => value
+                .as_bytes()
+                .map(|kid| Self::Kid(kid.clone()))
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn cose_key(&self) -> Option<&CoseKey> {
+        match self {
+            Self::CoseKey(cose_key) => Some(cose_key),
+            _ => None,
+        }
+    }
+
+    fn entry(&self) -> (i64, CborValue) {
+        match self {
+            Self::CoseKey(cose_key) => (CNF_COSE_KEY_LABEL, cose_key.to_cbor()),
+            Self::EncryptedCoseKey(encrypted) => (CNF_ENCRYPTED_COSE_KEY_LABEL, encrypted.clone()),
+            Self::Kid(kid) => (CNF_KID_LABEL, CborValue::Bytes(kid.clone())),
+        }
+    }
+}
+
+impl From<CoseKey> for Confirmation {
+    fn from(cose_key: CoseKey) -> Self {
+        Self::CoseKey(cose_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbor;
+
+    #[test]
+    fn should_convert_signing_keys() {
+        for alg in [
+            SigningAlgorithm::EdDsa,
+            SigningAlgorithm::Es256,
+            SigningAlgorithm::Es384,
+            SigningAlgorithm::Es512,
+        ] {
+            let key = SigningKey::generate(alg);
+            let cose_key = CoseKey::from(&key);
+            assert_eq!(cose_key.alg, Some(alg.cose_alg()));
+            assert_eq!(CoseKey::from_cbor(&cose_key.to_cbor()).unwrap(), cose_key);
+
+            let pk = VerifierPublicKey::try_from(&cose_key).unwrap();
+            assert_eq!(pk.to_bytes(), key.public_key().to_bytes());
+        }
+    }
+
+    #[test]
+    fn should_round_trip_confirmations() {
+        let symmetric = CoseKey::new(CoseKeyParams::Symmetric { k: vec![7; 32] }).with_kid(*b"k1");
+        for cnf in [
+            Confirmation::CoseKey(symmetric.clone()),
+            Confirmation::Kid(b"holder-1".to_vec()),
+            Confirmation::EncryptedCoseKey(CborValue::Tag(16, Box::new(CborValue::Array(vec![])))),
+        ] {
+            assert_eq!(Confirmation::from_cbor(&cnf.to_cbor()).unwrap(), cnf);
+            assert_eq!(Confirmation::from_cnf(&cnf.to_cnf()).unwrap(), cnf);
+        }
+        assert!(matches!(
+            VerifierPublicKey::try_from(&symmetric),
+            Err(SdCwtError::InvalidClaim(CNF_LABEL))
+        ));
+    }
+
+    #[test]
+    fn should_parse_cose_key() {
+        // RFC 8747 example of a P-256 key
+        let cose_key = cbor::from_slice(&[
+            0xa5, 0x01, 0x02, 0x02, 0x42, 0x11, 0x22, 0x20, 0x01, 0x21, 0x42, 0xaa, 0xbb, 0x22,
+            0x42, 0xcc, 0xdd,
+        ])
+        .unwrap();
+        let cose_key = CoseKey::from_cbor(&cose_key).unwrap();
+        assert_eq!(cose_key.kid.as_deref(), Some(&[0x11, 0x22][..]));
+        assert_eq!(cose_key.alg, None);
+        assert_eq!(
+            cose_key.params,
+            CoseKeyParams::Ec2 {
+                crv: COSE_CRV_P256,
+                x: vec![0xaa, 0xbb],
+                y: vec![0xcc, 0xdd]
+            }
+        );
+
+        let missing_x = CborValue::Map(vec![
+            (COSE_KEY_KTY.into(), COSE_KTY_OKP.into()),
+            (COSE_KEY_CRV.into(), COSE_CRV_ED25519.into()),
+        ]);
+        assert!(CoseKey::from_cbor(&missing_x).is_err());
+        let two_keys = CborValue::Map(vec![
+            (CNF_COSE_KEY_LABEL.into(), symmetric_key()),
+            (CNF_KID_LABEL.into(), CborValue::Bytes(vec![1])),
+        ]);
+        assert!(Confirmation::from_cbor(&two_keys).is_err());
+    }
+
+    fn symmetric_key() -> CborValue {
+        CoseKey::new(CoseKeyParams::Symmetric { k: vec![1] }).to_cbor()
+    }
+}
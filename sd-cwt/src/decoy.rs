@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::hash::HashAlgorithm;
+use crate::path::ClaimPath;
+
+/// How many decoy digests the issuer mixes with the digests of the redacted claims so that a
+/// verifier cannot tell how many claims were redacted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Decoys {
+    #[default]
+    None,
+    /// Same number of decoys in the claims set and in every map with redacted claims
+    Fixed(usize),
+    /// Random number of decoys, drawn for the claims set and for every map with redacted claims
+    Range(RangeInclusive<usize>),
+    /// Number of decoys for each map or array, designated by its path (the root path for the
+    /// claims set itself)
+    PerMap(HashMap<ClaimPath, usize>),
+}
+
+impl Decoys {
+    /// Paths of the maps receiving decoys besides the ones with redacted claims
+    pub(crate) fn map_paths(&self) -> Vec<ClaimPath> {
+        match self {
+            Self::None => vec![],
+            Self::Fixed(_) | Self::Range(_) => vec![ClaimPath::root()],
+            Self::PerMap(counts) => counts.keys().cloned().collect(),
+        }
+    }
+
+    pub(crate) fn count(&self, map_path: &ClaimPath) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Fixed(count) => *count,
+            Self::Range(range) if range.is_empty() => 0,
+            Self::Range(range) => rand::thread_rng().gen_range(range.clone()),
+            Self::PerMap(counts) => counts.get(map_path).copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Digest of random bytes, which cannot be told apart from the digest of an actual disclosure
+pub(crate) fn decoy_digest(hash_alg: HashAlgorithm) -> Vec<u8> {
+    hash_alg.digest(rand::random::<[u8; 32]>())
+}
+
+/// Inserts the digest at a random position so that its position does not leak whether it is a
+/// decoy or the one of a redacted claim
+pub(crate) fn insert_shuffled<T>(digests: &mut Vec<T>, digest: T) {
+    let index = rand::thread_rng().gen_range(0..=digests.len());
+    digests.insert(index, digest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_decoys() {
+        let (root, address) = (ClaimPath::root(), ClaimPath::root().join("address"));
+        assert_eq!(Decoys::None.count(&root), 0);
+        assert_eq!(Decoys::Fixed(3).count(&address), 3);
+        assert!((2..=4).contains(&Decoys::Range(2..=4).count(&root)));
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = Decoys::Range(4..=2);
+        assert_eq!(empty.count(&root), 0);
+        let per_map = Decoys::PerMap([(root.clone(), 1)].into());
+        assert_eq!(per_map.count(&root), 1);
+        assert_eq!(per_map.count(&address), 0);
+    }
+}
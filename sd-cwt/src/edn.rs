@@ -0,0 +1,800 @@
+use std::str::FromStr;
+
+use base64::Engine;
+use ciborium::Value as CborValue;
+use sd_cwt_types::{serialization::ToCBORBytes, Salted, SdPayload};
+
+use crate::cbor;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::labels::REGISTERED_CLAIMS;
+use crate::path::{ClaimPath, PathSegment};
+
+/// CBOR tag marking a claim key, a claim value or an array element as to be redacted
+pub const TO_BE_REDACTED_TAG: u64 = 58;
+
+/// Claims set written in CBOR diagnostic notation (EDN), as in the draft examples, where every
+/// disclosable claim key or array element is tagged as to be redacted e.g. `58(501): "ABCD"`
+#[derive(Debug, Clone)]
+pub struct EdnClaims {
+    /// Claims set as written, tags included
+    pub raw: CborValue,
+    /// JSON pointers of the disclosable claims and array elements, parents before their children
+    pub disclosable_paths: Vec<ClaimPath>,
+}
+
+impl EdnClaims {
+    /// Claims set without its to be redacted tags, as it is signed
+    pub fn to_cbor(&self) -> CborValue {
+        strip_tags(&self.raw)
+    }
+}
+
+impl FromStr for EdnClaims {
+    type Err = SdCwtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = parse(s)?;
+        let mut disclosable_paths = vec![];
+        collect_tagged_keys(&raw, &mut ClaimPath::root(), &mut disclosable_paths)?;
+        Ok(Self {
+            raw,
+            disclosable_paths,
+        })
+    }
+}
+
+fn collect_tagged_keys(
+    node: &CborValue,
+    path: &mut ClaimPath,
+    paths: &mut Vec<ClaimPath>,
+) -> SdCwtResult<()> {
+    match node {
+        CborValue::Map(entries) => {
+            for (key, value) in entries {
+                let segment = match key {
+                    CborValue::Tag(TO_BE_REDACTED_TAG, key) => {
+                        let segment =
+                            PathSegment::from_key(key).ok_or(SdCwtError::InvalidClaimKey)?;
+                        paths.push(path.join(segment.clone()));
+                        segment
+                    }
+                    key => match PathSegment::from_key(key) {
+                        Some(segment) => segment,
+                        None => continue,
+                    },
+                };
+                path.push(segment);
+                collect_tagged_keys(value, path, paths)?;
+                path.pop();
+            }
+        }
+        CborValue::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                path.push(index);
+                collect_tagged_keys(element, path, paths)?;
+                path.pop();
+            }
+        }
+        CborValue::Tag(TO_BE_REDACTED_TAG, value) => {
+            if !path.is_root() {
+                paths.push(path.clone());
+            }
+            collect_tagged_keys(value, path, paths)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn strip_tags(value: &CborValue) -> CborValue {
+    match value {
+        CborValue::Tag(TO_BE_REDACTED_TAG, value) => strip_tags(value),
+        CborValue::Tag(tag, value) => CborValue::Tag(*tag, Box::new(strip_tags(value))),
+        CborValue::Array(elements) => CborValue::Array(elements.iter().map(strip_tags).collect()),
+        CborValue::Map(entries) => CborValue::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (strip_tags(k), strip_tags(v)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Parses a single data item written in CBOR diagnostic notation. Comments, embedded CBOR
+/// (`<< >>`), `h''`, `b64''` and `''` byte strings, tags and encoding indicators are supported.
+pub fn parse(edn: &str) -> SdCwtResult<CborValue> {
+    let mut parser = Parser { edn, position: 0 };
+    let value = parser.value()?;
+    parser.skip_blanks()?;
+    if parser.position < edn.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    edn: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> SdCwtError {
+        SdCwtError::InvalidEdn {
+            offset: self.position,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.edn[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Consumes `token` when the remaining input starts with it
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> SdCwtResult<()> {
+        self.skip_blanks()?;
+        if !self.eat(token) {
+            return Err(self.error(&format!("expected '{token}'")));
+        }
+        Ok(())
+    }
+
+    /// Skips whitespace, `/ block /` comments and `# end of line` comments
+    fn skip_blanks(&mut self) -> SdCwtResult<()> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if self.eat("/") {
+                let end = self
+                    .rest()
+                    .find('/')
+                    .ok_or_else(|| self.error("unclosed comment"))?;
+                self.position += end + 1;
+            } else if self.eat("#") {
+                self.position += self.rest().find('\n').unwrap_or(self.rest().len());
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skips an encoding indicator e.g. `_1`, which has no bearing on the data model
+    fn skip_encoding_indicator(&mut self) {
+        let rest = self.rest();
+        if let Some(indicator) = rest.strip_prefix('_') {
+            let digits = indicator.len()
+                - indicator
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            self.position += 1 + digits;
+        }
+    }
+
+    /// Parses comma separated items until `end`, a trailing comma being tolerated
+    fn items<T>(
+        &mut self,
+        end: &str,
+        mut item: impl FnMut(&mut Self) -> SdCwtResult<T>,
+    ) -> SdCwtResult<Vec<T>> {
+        let mut items = vec![];
+        loop {
+            self.skip_blanks()?;
+            if self.eat(end) {
+                return Ok(items);
+            }
+            items.push(item(self)?);
+            self.skip_blanks()?;
+            if !self.eat(",") {
+                self.expect(end)?;
+                return Ok(items);
+            }
+        }
+    }
+
+    fn value(&mut self) -> SdCwtResult<CborValue> {
+        self.skip_blanks()?;
+        let Some(first) = self.peek() else {
+            return Err(self.error("expected a data item"));
+        };
+        match first {
+            '[' => {
+                self.position += 1;
+                self.skip_encoding_indicator();
+                Ok(CborValue::Array(self.items("]", Self::value)?))
+            }
+            '{' => {
+                self.position += 1;
+                self.skip_encoding_indicator();
+                let entries = self.items("}", |parser| {
+                    let key = parser.value()?;
+                    parser.expect(":")?;
+                    Ok((key, parser.value()?))
+                })?;
+                Ok(CborValue::Map(entries))
+            }
+            '"' => Ok(CborValue::Text(self.text('"')?)),
+            '\'' => Ok(CborValue::Bytes(self.text('\'')?.into_bytes())),
+            '<' if self.eat("<<") => {
+                let items = self.items(">>", Self::value)?;
+                let bytes = items
+                    .iter()
+                    .map(cbor::to_vec)
+                    .collect::<SdCwtResult<Vec<_>>>()?;
+                Ok(CborValue::Bytes(bytes.concat()))
+            }
+            '(' if self.eat("(_") => self.chunks(),
+            'h' if self.eat("h'") => self.hex(),
+            'b' if self.eat("b64'") => self.base64(),
+            c if c.is_ascii_digit() || c == '-' || c == '+' => self.number(),
+            _ => self.keyword(),
+        }
+    }
+
+    /// Text of a string quoted with `quote`, escapes resolved
+    fn text(&mut self, quote: char) -> SdCwtResult<String> {
+        self.position += 1;
+        let mut text = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                c if c == quote => {
+                    self.position += i + 1;
+                    return Ok(text);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let mut units = vec![code_unit(&mut chars)];
+                            if units[0].is_some_and(|unit| (0xd800..0xdc00).contains(&unit)) {
+                                let next =
+                                    chars.by_ref().take(2).map(|(_, c)| c).collect::<String>();
+                                units.push(
+                                    Some(next)
+                                        .filter(|n| n == "\\u")
+                                        .and_then(|_| code_unit(&mut chars)),
+                                );
+                            }
+                            let units = units
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                .ok_or_else(|| self.error("invalid escape"))?;
+                            text.push_str(
+                                &String::from_utf16(&units)
+                                    .map_err(|_| self.error("unpaired surrogate"))?,
+                            );
+                            continue;
+                        }
+                        Some(c) => c,
+                        None => break,
+                    };
+                    text.push(escaped);
+                }
+                c => text.push(c),
+            }
+        }
+        Err(self.error("unclosed string"))
+    }
+
+    /// Content of a `h''` byte string, whitespace and comments being allowed in between digits
+    fn hex(&mut self) -> SdCwtResult<CborValue> {
+        let end = self
+            .rest()
+            .find('\'')
+            .ok_or_else(|| self.error("unclosed byte string"))?;
+        let mut digits = String::new();
+        let mut in_comment = false;
+        for c in self.rest()[..end].chars() {
+            match c {
+                '/' => in_comment = !in_comment,
+                c if in_comment || c.is_whitespace() => {}
+                c => digits.push(c),
+            }
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Err(self.error("odd number of hex digits"));
+        }
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| self.error("invalid hex digit"))?;
+        self.position += end + 1;
+        Ok(CborValue::Bytes(bytes))
+    }
+
+    /// Content of a `b64''` byte string, in either the standard or the URL-safe alphabet
+    fn base64(&mut self) -> SdCwtResult<CborValue> {
+        let end = self
+            .rest()
+            .find('\'')
+            .ok_or_else(|| self.error("unclosed byte string"))?;
+        let encoded = self.rest()[..end]
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '=')
+            .map(|c| match c {
+                '+' => '-',
+                '/' => '_',
+                c => c,
+            })
+            .collect::<String>();
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| self.error("invalid base64"))?;
+        self.position += end + 1;
+        Ok(CborValue::Bytes(bytes))
+    }
+
+    /// Indefinite length string written as its chunks e.g. `(_ h'01', h'02')`
+    fn chunks(&mut self) -> SdCwtResult<CborValue> {
+        let chunks = self.items(")", Self::value)?;
+        if chunks.iter().all(CborValue::is_text) {
+            Ok(CborValue::Text(
+                chunks.iter().filter_map(CborValue::as_text).collect(),
+            ))
+        } else if chunks.iter().all(CborValue::is_bytes) {
+            Ok(CborValue::Bytes(
+                chunks
+                    .iter()
+                    .filter_map(CborValue::as_bytes)
+                    .flatten()
+                    .copied()
+                    .collect(),
+            ))
+        } else {
+            Err(self.error("chunks must all be text or byte strings"))
+        }
+    }
+
+    /// Integer, float or tag number followed by its tagged data item
+    fn number(&mut self) -> SdCwtResult<CborValue> {
+        let start = self.position;
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')))
+            .unwrap_or(self.rest().len());
+        let token = self.rest()[..len].to_string();
+        self.position += len;
+
+        let (negative, unsigned) = match token.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, token.strip_prefix('+').unwrap_or(&token)),
+        };
+        let radix = match unsigned.get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        };
+        let integer = match radix {
+            Some(radix) => u128::from_str_radix(&unsigned[2..], radix).ok(),
+            None => unsigned.parse::<u128>().ok(),
+        };
+
+        let value = match integer {
+            Some(integer) => {
+                let integer = i128::try_from(integer)
+                    .ok()
+                    .map(|i| if negative { -i } else { i });
+                let integer = integer
+                    .and_then(|i| ciborium::value::Integer::try_from(i).ok())
+                    .ok_or_else(|| self.error("integer out of range"))?;
+                CborValue::Integer(integer)
+            }
+            None if unsigned == "Infinity" => CborValue::Float(if negative {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            }),
+            None => CborValue::Float(token.parse::<f64>().map_err(|_| {
+                self.position = start;
+                self.error("invalid number")
+            })?),
+        };
+        self.skip_encoding_indicator();
+
+        if self.eat("(") {
+            let tag = match (&value, negative) {
+                (CborValue::Integer(tag), false) => u64::try_from(*tag).ok(),
+                _ => None,
+            }
+            .ok_or_else(|| self.error("invalid tag number"))?;
+            let tagged = self.value()?;
+            self.expect(")")?;
+            return Ok(CborValue::Tag(tag, Box::new(tagged)));
+        }
+        Ok(value)
+    }
+
+    fn keyword(&mut self) -> SdCwtResult<CborValue> {
+        let keywords = [
+            ("true", CborValue::Bool(true)),
+            ("false", CborValue::Bool(false)),
+            ("null", CborValue::Null),
+            ("NaN", CborValue::Float(f64::NAN)),
+            ("Infinity", CborValue::Float(f64::INFINITY)),
+        ];
+        for (keyword, value) in keywords {
+            if self.eat(keyword) {
+                self.skip_encoding_indicator();
+                return Ok(value);
+            }
+        }
+        Err(self.error("unexpected character"))
+    }
+}
+
+/// UTF-16 code unit of a `\uXXXX` escape
+fn code_unit(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u16> {
+    let hex = chars.take(4).map(|(_, c)| c).collect::<String>();
+    u16::from_str_radix(&hex, 16).ok()
+}
+
+/// Renders SD-CWT structures in pretty CBOR diagnostic notation, with the embedded CBOR of
+/// `bstr .cbor` fields between `<< >>` and the well-known labels named in comments, so that they
+/// can be compared with the examples of the draft
+pub trait ToEdn {
+    fn to_edn(&self) -> String;
+}
+
+impl ToEdn for crate::SdCwt {
+    fn to_edn(&self) -> String {
+        render(self.as_bytes(), Shape::SdCwt)
+    }
+}
+
+impl ToEdn for sd_cwt_types::SdCwt {
+    fn to_edn(&self) -> String {
+        render(&self.to_cbor_bytes(), Shape::SdCwt)
+    }
+}
+
+impl ToEdn for SdPayload {
+    fn to_edn(&self) -> String {
+        render(&self.to_cbor_bytes(), Shape::Payload)
+    }
+}
+
+impl ToEdn for Salted {
+    fn to_edn(&self) -> String {
+        render(&self.to_cbor_bytes(), Shape::Salted)
+    }
+}
+
+/// What a data item stands for in an SD-CWT, telling which byte strings hold embedded CBOR and
+/// how to name the labels of a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Any,
+    SdCwt,
+    Protected,
+    Unprotected,
+    Payload,
+    SdClaims,
+    Salted,
+}
+
+impl Shape {
+    fn label_name(self, label: i64) -> Option<&'static str> {
+        match (self, label) {
+            (Self::Protected, 1) => Some("alg"),
+            (Self::Protected, 16) => Some("typ"),
+            (Self::Unprotected, 1111) => Some("sd_claims"),
+            (Self::Unprotected, 1112) => Some("sd_kbt"),
+            (Self::Payload, 1113) => Some("sd_hash"),
+            (Self::Payload, 1114) => Some("sd_alg"),
+            (Self::Payload, 1115) => Some("redacted_keys"),
+            (Self::Payload, label) => REGISTERED_CLAIMS
+                .iter()
+                .find(|(_, l)| *l == label)
+                .map(|(name, _)| *name),
+            _ => None,
+        }
+    }
+
+    fn entry_shape(self, label: Option<i64>) -> Self {
+        match (self, label) {
+            (Self::Unprotected, Some(1111)) => Self::SdClaims,
+            (Self::Unprotected, Some(1112)) => Self::SdCwt,
+            _ => Self::Any,
+        }
+    }
+}
+
+fn render(bytes: &[u8], shape: Shape) -> String {
+    let mut edn = String::new();
+    match cbor::from_slice(bytes) {
+        Ok(value) => write_value(&mut edn, &value, shape, 0),
+        Err(_) => write_hex(&mut edn, bytes),
+    }
+    edn
+}
+
+fn write_value(edn: &mut String, value: &CborValue, shape: Shape, indent: usize) {
+    match (value, shape) {
+        (CborValue::Bytes(bytes), Shape::SdCwt | Shape::Protected | Shape::Payload) => {
+            write_embedded(edn, bytes, shape, indent)
+        }
+        (CborValue::Bytes(bytes), Shape::Salted) => write_embedded(edn, bytes, Shape::Any, indent),
+        (CborValue::Tag(tag, value), _) => {
+            edn.push_str(&format!("{tag}("));
+            write_value(edn, value, shape, indent);
+            edn.push(')');
+        }
+        (CborValue::Array(elements), Shape::SdCwt) if elements.len() == 4 => {
+            let fields = [
+                ("protected", Shape::Protected),
+                ("unprotected", Shape::Unprotected),
+                ("payload", Shape::Payload),
+                ("signature", Shape::Any),
+            ];
+            write_container(
+                edn,
+                ('[', ']'),
+                elements.iter().zip(fields),
+                indent,
+                |edn, (element, (name, shape))| {
+                    edn.push_str(&format!("/ {name} / "));
+                    write_value(edn, element, shape, indent + 1);
+                },
+            );
+        }
+        (CborValue::Array(elements), _) => {
+            let shape = match shape {
+                Shape::SdClaims => Shape::Salted,
+                _ => Shape::Any,
+            };
+            write_container(edn, ('[', ']'), elements.iter(), indent, |edn, element| {
+                write_value(edn, element, shape, indent + 1)
+            });
+        }
+        (CborValue::Map(entries), _) => {
+            write_container(
+                edn,
+                ('{', '}'),
+                entries.iter(),
+                indent,
+                |edn, (key, value)| {
+                    let label = key.as_integer().and_then(|i| i64::try_from(i).ok());
+                    if let Some(name) = label.and_then(|label| shape.label_name(label)) {
+                        edn.push_str(&format!("/ {name} / "));
+                    }
+                    write_value(edn, key, Shape::Any, indent + 1);
+                    edn.push_str(": ");
+                    write_value(edn, value, shape.entry_shape(label), indent + 1);
+                },
+            );
+        }
+        (CborValue::Bytes(bytes), _) => write_hex(edn, bytes),
+        (CborValue::Integer(i), _) => edn.push_str(&i128::from(*i).to_string()),
+        (CborValue::Text(text), _) => {
+            // a JSON string is a valid EDN text string
+            edn.push_str(&serde_json::Value::from(text.as_str()).to_string())
+        }
+        (CborValue::Float(f), _) if f.is_nan() => edn.push_str("NaN"),
+        (CborValue::Float(f), _) if f.is_infinite() => {
+            edn.push_str(if *f > 0.0 { "Infinity" } else { "-Infinity" })
+        }
+        (CborValue::Float(f), _) => edn.push_str(&format!("{f:?}")),
+        (CborValue::Bool(b), _) => edn.push_str(&b.to_string()),
+        (CborValue::Null, _) => edn.push_str("null"),
+        (_, _) => edn.push_str("undefined"),
+    }
+}
+
+/// Writes the embedded CBOR data items of a `bstr .cbor` field, or the plain byte string when it
+/// does not hold well-formed CBOR
+fn write_embedded(edn: &mut String, bytes: &[u8], shape: Shape, indent: usize) {
+    let mut reader = bytes;
+    let mut items = vec![];
+    while !reader.is_empty() {
+        match ciborium::from_reader::<CborValue, _>(&mut reader) {
+            Ok(item) => items.push(item),
+            Err(_) => return write_hex(edn, bytes),
+        }
+    }
+    if items.is_empty() {
+        return write_hex(edn, bytes);
+    }
+    edn.push_str("<< ");
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            edn.push_str(", ");
+        }
+        write_value(edn, item, shape, indent);
+    }
+    edn.push_str(" >>");
+}
+
+fn write_container<T>(
+    edn: &mut String,
+    (open, close): (char, char),
+    items: impl ExactSizeIterator<Item = T>,
+    indent: usize,
+    mut write_item: impl FnMut(&mut String, T),
+) {
+    edn.push(open);
+    let len = items.len();
+    for (i, item) in items.enumerate() {
+        edn.push('\n');
+        edn.push_str(&INDENT.repeat(indent + 1));
+        write_item(edn, item);
+        if i + 1 < len {
+            edn.push(',');
+        }
+    }
+    if len > 0 {
+        edn.push('\n');
+        edn.push_str(&INDENT.repeat(indent));
+    }
+    edn.push(close);
+}
+
+const INDENT: &str = "  ";
+
+fn write_hex(edn: &mut String, bytes: &[u8]) {
+    edn.push_str("h'");
+    for byte in bytes {
+        edn.push_str(&format!("{byte:02x}"));
+    }
+    edn.push('\'');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbor::IntoInt;
+    use crate::{IssuerPrivateKey, SdCwt};
+    use sd_cwt_types::{ordered_hash_map::OrderedHashMap, serialization::Deserialize};
+
+    const CLAIMS: &str = r#"
+        {
+            / aud / 3: "https://verifier.example",
+            / most_recent_inspection_passed / 500: true,
+            / inspector_license_number / 58(501): "ABCD-123456",
+            / inspection_dates / 502: [
+                58(1549560720),
+                1612560720, # not redacted
+            ],
+            / inspection_location / 58(503): {
+                "country": "us",
+                58("region"): "ca",
+                "postal_code": '94188'
+            },
+            "nonce": h'6e6f 6e63 65 / ascii /',
+            "embedded": << 1, "two" >>,
+        }
+    "#;
+
+    #[test]
+    fn should_parse_to_be_redacted_claims() {
+        let claims = CLAIMS.parse::<EdnClaims>().unwrap();
+        assert_eq!(
+            claims.disclosable_paths,
+            vec!["/501", "/502/0", "/503", "/503/region"]
+        );
+
+        let expected = CborValue::Map(vec![
+            (3.into(), "https://verifier.example".into()),
+            (500.into(), true.into()),
+            (501.into(), "ABCD-123456".into()),
+            (
+                502.into(),
+                CborValue::Array(vec![1549560720.into(), 1612560720.into()]),
+            ),
+            (
+                503.into(),
+                CborValue::Map(vec![
+                    ("country".into(), "us".into()),
+                    ("region".into(), "ca".into()),
+                    ("postal_code".into(), CborValue::Bytes(b"94188".to_vec())),
+                ]),
+            ),
+            ("nonce".into(), CborValue::Bytes(b"nonce".to_vec())),
+            (
+                "embedded".into(),
+                CborValue::Bytes(vec![0x01, 0x63, b't', b'w', b'o']),
+            ),
+        ]);
+        assert_eq!(claims.to_cbor(), expected);
+    }
+
+    #[test]
+    fn should_parse_scalars() {
+        let value =
+            parse(r#"[-1, 0x1f, 1.5, -Infinity, "é\n", b64'AQI', (_ "a", "b"), 1(0), null]"#)
+                .unwrap();
+        assert_eq!(
+            value,
+            CborValue::Array(vec![
+                (-1).into(),
+                31.into(),
+                1.5.into(),
+                f64::NEG_INFINITY.into(),
+                "é\n".into(),
+                CborValue::Bytes(vec![1, 2]),
+                "ab".into(),
+                CborValue::Tag(1, Box::new(0.into())),
+                CborValue::Null,
+            ])
+        );
+    }
+
+    #[test]
+    fn should_fail_with_invalid_edn() {
+        assert!(matches!(
+            parse(r#"{ 1: "a" "#),
+            Err(SdCwtError::InvalidEdn { offset: 9, .. })
+        ));
+        assert!(matches!(
+            parse("h'abc'"),
+            Err(SdCwtError::InvalidEdn { .. })
+        ));
+        assert!(matches!(
+            "{ 58([1]): 2 }".parse::<EdnClaims>(),
+            Err(SdCwtError::InvalidClaimKey)
+        ));
+    }
+
+    #[test]
+    fn should_render_sd_cwt_with_embedded_cbor() {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_cwt = issuer.sign_edn(CLAIMS.parse().unwrap()).unwrap();
+
+        let edn = sd_cwt.to_edn();
+        assert!(edn.starts_with("18([\n  / protected / << {\n    / alg / 1: -8,"));
+        assert!(edn.contains("/ sd_claims / 1111: [\n      << [\n        h'"));
+        assert!(edn.contains("/ payload / << {\n    / aud / 3: \"https://verifier.example\","));
+        assert!(edn.contains("/ redacted_keys / 1115: [\n"));
+        // only the byte strings known to hold CBOR are rendered as embedded CBOR
+        assert!(edn.contains("\"embedded\": h'016374776f'"));
+
+        // parsing the rendered EDN gives back the exact same encoding
+        let parsed = cbor::to_vec(&parse(&edn).unwrap()).unwrap();
+        assert_eq!(parsed, sd_cwt.as_bytes());
+
+        let typed = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        assert_eq!(typed.to_edn(), edn);
+        assert_eq!(SdCwt::from(parsed).to_edn(), edn,);
+    }
+
+    #[test]
+    fn should_render_payload_and_disclosure() {
+        let mut payload = SdPayload::new(
+            "https://verifier.example".to_string(),
+            1700000000.into_int(),
+            OrderedHashMap::new(),
+        );
+        payload.iss = Some("https://issuer.example".to_string());
+        assert_eq!(
+            payload.to_edn(),
+            "{\n  / iss / 1: \"https://issuer.example\",\n  / aud / 3: \"https://verifier.example\",\n  / iat / 6: 1700000000\n}"
+        );
+
+        let element =
+            sd_cwt_types::SaltedElementItem::new(vec![0; 16], CborValue::from("US").into())
+                .unwrap();
+        let disclosure = Salted::new_salted_element(element);
+        assert_eq!(
+            disclosure.to_edn(),
+            "<< [\n  h'00000000000000000000000000000000',\n  \"US\"\n] >>"
+        );
+    }
+}
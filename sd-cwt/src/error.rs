@@ -1,13 +1,87 @@
+use crate::path::ClaimPath;
+
 pub type SdCwtResult<T> = Result<T, SdCwtError>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum SdCwtError {
     #[error("Invalid YAML input provided {0}")]
     InvalidYamlInput(serde_yaml::value::Tag),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Invalid EDN at offset {offset}: {reason}")]
+    InvalidEdn { offset: usize, reason: String },
     #[error(transparent)]
     JwtSimpleError(#[from] jwt_simple::Error),
     #[error(transparent)]
     YamlError(#[from] serde_yaml::Error),
-    #[error("CborError")]
-    CborError,
-}
\ No newline at end of file
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    DeserializeError(#[from] sd_cwt_types::error::DeserializeError),
+    #[error(transparent)]
+    BuilderError(#[from] sd_cwt_types::BuilderError),
+    #[error("Invalid CBOR: {0}")]
+    CborError(String),
+    #[error("Claims set must be a map")]
+    InvalidClaimsSet,
+    #[error("Claim keys must be either integers or text")]
+    InvalidClaimKey,
+    #[error("Claim {0} has an invalid type")]
+    InvalidClaim(i64),
+    #[error("Claim {0} is reserved by SD-CWT")]
+    ReservedClaim(i64),
+    #[error("Claim {0} is set more than once")]
+    DuplicateClaim(String),
+    #[error("Mandatory claim {0} is missing")]
+    MissingClaim(i64),
+    #[error("Invalid claim path {0}")]
+    InvalidClaimPath(String),
+    #[error("Disclosable path {0} does not point to any claim")]
+    InvalidDisclosablePath(ClaimPath),
+    #[error("Selectively disclosing {0} is not supported")]
+    UnsupportedDisclosablePath(String),
+    #[error("Signing algorithm {0} is not supported")]
+    UnsupportedSigningAlgorithm(String),
+    #[error("Signing algorithm {found} does not match the {expected} key")]
+    SigningAlgorithmMismatch { expected: i64, found: i64 },
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+    #[error("Hash algorithm {0} is not supported")]
+    UnsupportedHashAlgorithm(String),
+    #[error("Invalid signature")]
+    InvalidSignature,
+    #[error("Digest {0:x?} appears more than once")]
+    DuplicateDigest(Vec<u8>),
+    #[error("Disclosure with digest {0:x?} does not match any redacted claim")]
+    UnreferencedDisclosure(Vec<u8>),
+    #[error("No disclosure matches claim path {0}")]
+    UnknownClaimPath(ClaimPath),
+    #[error("Token expired at {exp}, it is now {now}")]
+    Expired { exp: i64, now: i64 },
+    #[error("Token is not valid before {nbf}, it is now {now}")]
+    NotYetValid { nbf: i64, now: i64 },
+    #[error("Token is issued in the future at {iat}, it is now {now}")]
+    IssuedInFuture { iat: i64, now: i64 },
+    #[error("Token issued at {iat} is older than {max_age}s")]
+    TooOld { iat: i64, max_age: u64 },
+    #[error("Token has no expiration time")]
+    MissingExpiration,
+    #[error("Token has no issuance time")]
+    MissingIssuedAt,
+    #[error("Audience {0} is not accepted")]
+    UnacceptedAudience(String),
+    #[error("Issuer {0:?} is not accepted")]
+    UnacceptedIssuer(Option<String>),
+    #[error("Presentation is not bound to its holder by a key binding token")]
+    MissingKeyBinding,
+    #[error("No holder key confirmed in the cnf claim")]
+    MissingHolderKey,
+    #[error("Invalid key binding token")]
+    InvalidKeyBinding,
+    #[error("Key binding token is intended for audience {0}")]
+    KeyBindingAudienceMismatch(String),
+    #[error("Key binding token nonce does not match")]
+    KeyBindingNonceMismatch,
+    #[error("Key binding token does not cover this presentation")]
+    KeyBindingHashMismatch,
+}
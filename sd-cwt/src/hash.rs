@@ -0,0 +1,111 @@
+use sd_cwt_types::Int;
+use sha2::Digest;
+
+use crate::cbor::int_to_i64;
+use crate::error::{SdCwtError, SdCwtResult};
+
+/// COSE algorithm identifier for SHA-256
+pub const COSE_ALG_SHA256: i64 = -16;
+/// COSE algorithm identifier for SHA-384
+pub const COSE_ALG_SHA384: i64 = -43;
+/// COSE algorithm identifier for SHA-512
+pub const COSE_ALG_SHA512: i64 = -44;
+/// No COSE algorithm identifier is registered for SHA3-256 yet so one from the private use range
+/// is used
+pub const COSE_ALG_SHA3_256: i64 = -65537;
+
+/// Hash algorithm used to compute the digests of the disclosures, advertised in the `sd_alg` claim
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+}
+
+impl HashAlgorithm {
+    pub fn cose_alg(&self) -> i64 {
+        match self {
+            Self::Sha256 => COSE_ALG_SHA256,
+            Self::Sha384 => COSE_ALG_SHA384,
+            Self::Sha512 => COSE_ALG_SHA512,
+            Self::Sha3_256 => COSE_ALG_SHA3_256,
+        }
+    }
+
+    pub fn digest(&self, data: impl AsRef<[u8]>) -> Vec<u8> {
+        match self {
+            Self::Sha256 => hash::<sha2::Sha256>(data),
+            Self::Sha384 => hash::<sha2::Sha384>(data),
+            Self::Sha512 => hash::<sha2::Sha512>(data),
+            Self::Sha3_256 => hash::<sha3::Sha3_256>(data),
+        }
+    }
+
+    /// Algorithm advertised by `sd_alg`, SHA-256 when absent
+    pub fn from_sd_alg(sd_alg: Option<&Int>) -> SdCwtResult<Self> {
+        match sd_alg {
+            Some(alg) => int_to_i64(alg)
+                .ok_or_else(|| SdCwtError::UnsupportedHashAlgorithm(alg.to_string()))
+                .and_then(Self::try_from),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+impl TryFrom<i64> for HashAlgorithm {
+    type Error = SdCwtError;
+
+    fn try_from(alg: i64) -> SdCwtResult<Self> {
+        match alg {
+            COSE_ALG_SHA256 => Ok(Self::Sha256),
+            COSE_ALG_SHA384 => Ok(Self::Sha384),
+            COSE_ALG_SHA512 => Ok(Self::Sha512),
+            COSE_ALG_SHA3_256 => Ok(Self::Sha3_256),
+            _ => Err(SdCwtError::UnsupportedHashAlgorithm(alg.to_string())),
+        }
+    }
+}
+
+fn hash<D: Digest>(data: impl AsRef<[u8]>) -> Vec<u8> {
+    D::digest(data).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_cose_alg() {
+        for alg in [
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha384,
+            HashAlgorithm::Sha512,
+            HashAlgorithm::Sha3_256,
+        ] {
+            assert_eq!(HashAlgorithm::try_from(alg.cose_alg()).unwrap(), alg);
+        }
+    }
+
+    #[test]
+    fn should_digest() {
+        let digest_len = |alg: HashAlgorithm| alg.digest(b"abc").len();
+        assert_eq!(digest_len(HashAlgorithm::Sha256), 32);
+        assert_eq!(digest_len(HashAlgorithm::Sha384), 48);
+        assert_eq!(digest_len(HashAlgorithm::Sha512), 64);
+        assert_eq!(digest_len(HashAlgorithm::Sha3_256), 32);
+        assert_ne!(
+            HashAlgorithm::Sha256.digest(b"abc"),
+            HashAlgorithm::Sha3_256.digest(b"abc")
+        );
+    }
+
+    #[test]
+    fn should_fail_with_unsupported_alg() {
+        assert!(matches!(
+            HashAlgorithm::try_from(-18),
+            Err(SdCwtError::UnsupportedHashAlgorithm(alg)) if alg == "-18"
+        ));
+    }
+}
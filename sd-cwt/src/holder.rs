@@ -0,0 +1,476 @@
+use crate::cbor::{self, int_to_i64, IntoInt};
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::hash::HashAlgorithm;
+use crate::issuer::{redacted_element_digest, REDACTED_KEYS_LABEL, SD_CLAIMS_LABEL};
+use crate::path::{ClaimPath, PathSegment};
+use crate::SdCwt;
+use ciborium::Value as CborValue;
+use sd_cwt_types::{serialization::ToCBORBytes, Anyy, IntOrText, Salted};
+use std::collections::HashMap;
+
+/// A disclosure carried by an SD-CWT along with the path of the claim it discloses
+#[derive(Debug, Clone)]
+pub struct Disclosure {
+    pub path: ClaimPath,
+    pub digest: Vec<u8>,
+    pub salted: Salted,
+}
+
+impl SdCwt {
+    /// Lists the disclosures carried in the unprotected header. The path of an array element is
+    /// its index within the issued array, which might include decoys.
+    pub fn disclosures(&self) -> SdCwtResult<Vec<Disclosure>> {
+        let sd_cwt = self.decode()?;
+        let hash_alg = HashAlgorithm::from_sd_alg(sd_cwt.payload.sd_alg.as_ref())?;
+
+        // where each digest is referenced from: the parent path of a redacted key or the path of a
+        // redacted element
+        let mut references = sd_cwt
+            .payload
+            .redacted_keys
+            .unwrap_or_default()
+            .into_iter()
+            .map(|digest| (digest, ClaimPath::root()))
+            .collect::<HashMap<_, _>>();
+        for (key, value) in sd_cwt.payload.custom.iter() {
+            if let Some(segment) = PathSegment::from_keyy(key) {
+                collect_references(&ClaimPath::root().join(segment), value, &mut references);
+            }
+        }
+
+        let salted = sd_cwt.unprotected.sd_claims.unwrap_or_default();
+        let digests = salted
+            .iter()
+            .map(|salted| hash_alg.digest(salted.to_cbor_bytes()))
+            .collect::<Vec<_>>();
+        let mut paths = vec![None; salted.len()];
+        // disclosed values can themselves reference other disclosures
+        while paths.iter().any(Option::is_none) {
+            let mut resolved = false;
+            for (i, salted) in salted.iter().enumerate() {
+                let Some(reference) = paths[i]
+                    .is_none()
+                    .then(|| references.get(&digests[i]).cloned())
+                    .flatten()
+                else {
+                    continue;
+                };
+                let (path, value) = match salted {
+                    Salted::SaltedClaim { salted_claim, .. } => {
+                        let name = match &salted_claim.index_1 {
+                            IntOrText::Int(i) => {
+                                PathSegment::Int(int_to_i64(i).ok_or(SdCwtError::InvalidClaimKey)?)
+                            }
+                            IntOrText::Text { text, .. } => PathSegment::Text(text.clone()),
+                        };
+                        (reference.join(name), &salted_claim.value)
+                    }
+                    Salted::SaltedElement { salted_element, .. } => {
+                        (reference, &salted_element.value)
+                    }
+                };
+                collect_references(&path, value, &mut references);
+                paths[i] = Some(path);
+                resolved = true;
+            }
+            if !resolved {
+                break;
+            }
+        }
+
+        salted
+            .into_iter()
+            .zip(digests)
+            .zip(paths)
+            .map(|((salted, digest), path)| match path {
+                Some(path) => Ok(Disclosure {
+                    path,
+                    digest,
+                    salted,
+                }),
+                None => Err(SdCwtError::UnreferencedDisclosure(digest)),
+            })
+            .collect()
+    }
+
+    /// Builds a presentation revealing only the claims at the given paths e.g. `/given_name`.
+    /// Revealing a nested claim also reveals the redacted claims it is nested in.
+    pub fn present(&self, paths: &[ClaimPath]) -> SdCwtResult<SdCwt> {
+        let disclosures = self.disclosures()?;
+        if let Some(unknown) = paths
+            .iter()
+            .find(|p| !disclosures.iter().any(|d| d.path == **p))
+        {
+            return Err(SdCwtError::UnknownClaimPath(unknown.clone()));
+        }
+        self.retain_disclosures(&disclosures, |d| paths.contains(&d.path))
+    }
+
+    /// Builds a presentation revealing only the disclosures matching the predicate along with the
+    /// ones they are nested in
+    pub fn present_with(&self, predicate: impl Fn(&Disclosure) -> bool) -> SdCwtResult<SdCwt> {
+        let disclosures = self.disclosures()?;
+        self.retain_disclosures(&disclosures, predicate)
+    }
+
+    /// Drops the unselected disclosures while leaving the signed protected header and payload
+    /// bytes untouched
+    fn retain_disclosures(
+        &self,
+        disclosures: &[Disclosure],
+        predicate: impl Fn(&Disclosure) -> bool,
+    ) -> SdCwtResult<SdCwt> {
+        let mut sd_cwt = cbor::from_slice(self.as_bytes())?;
+        let unprotected = cbor::unprotected_mut(&mut sd_cwt)?;
+
+        let sd_claims_label = CborValue::from(SD_CLAIMS_LABEL);
+        if let Some(position) = unprotected.iter().position(|(k, _)| *k == sd_claims_label) {
+            let CborValue::Array(sd_claims) = &mut unprotected[position].1 else {
+                return Err(SdCwtError::CborError("sd_claims must be an array".into()));
+            };
+            let selected = disclosures
+                .iter()
+                .filter(|d| predicate(d))
+                .map(|d| &d.path)
+                .collect::<Vec<_>>();
+            // a disclosure has to be kept when it is selected or when a selected one is nested in it
+            let is_kept = |d: &Disclosure| selected.iter().any(|path| path.starts_with(&d.path));
+            // disclosures are listed in the same order as in the SD-CWT
+            let mut selection = disclosures.iter().map(is_kept);
+            sd_claims.retain(|_| selection.next().unwrap_or_default());
+            if sd_claims.is_empty() {
+                unprotected.remove(position);
+            }
+        }
+
+        Ok(SdCwt(cbor::to_vec(&sd_cwt)?))
+    }
+}
+
+/// Records the path of every redacted element within `value` and the parent path of every
+/// redacted key
+fn collect_references(
+    path: &ClaimPath,
+    value: &Anyy,
+    references: &mut HashMap<Vec<u8>, ClaimPath>,
+) {
+    match value {
+        Anyy::Array { elements, .. } => {
+            for (i, element) in elements.iter().enumerate() {
+                let path = path.join(i);
+                match redacted_element_digest(element) {
+                    Some(digest) => {
+                        references.insert(digest.to_vec(), path);
+                    }
+                    None => collect_references(&path, element, references),
+                }
+            }
+        }
+        Anyy::Map { entries, .. } => {
+            let redacted_keys_label = Anyy::new_int(REDACTED_KEYS_LABEL.into_int());
+            for (key, value) in entries {
+                if *key == redacted_keys_label {
+                    if let Anyy::Array { elements, .. } = value {
+                        for element in elements {
+                            if let Anyy::Bytes { bytes, .. } = element {
+                                references.insert(bytes.clone(), path.clone());
+                            }
+                        }
+                    }
+                    continue;
+                }
+                let segment = match key {
+                    Anyy::Int(i) => match int_to_i64(i) {
+                        Some(i) => PathSegment::Int(i),
+                        None => continue,
+                    },
+                    Anyy::Text { text, .. } => PathSegment::Text(text.clone()),
+                    _ => continue,
+                };
+                collect_references(&path.join(segment), value, references);
+            }
+        }
+        Anyy::Tag { value, .. } => collect_references(path, value, references),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputClaims;
+    use crate::{Decoys, IssuerPrivateKey};
+    use sd_cwt_types::Keyy;
+
+    const YAML: &str = r#"
+        3: https://verifier.example
+        !sd given_name: John
+        !sd family_name: Doe
+        !sd email: "johndoe@example.com"
+        phone_number: "+1-202-555-0101"
+    "#;
+
+    fn issue() -> (IssuerPrivateKey, SdCwt) {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_cwt = issuer.sign(YAML.parse::<InputClaims>().unwrap()).unwrap();
+        (issuer, sd_cwt)
+    }
+
+    fn has_claim(payload: &sd_cwt_types::SdPayload, name: &str) -> bool {
+        payload
+            .custom
+            .contains_key(&Keyy::new_text(name.to_string()))
+    }
+
+    fn signed_parts(sd_cwt: &SdCwt) -> Vec<CborValue> {
+        let CborValue::Tag(18, cose_sign1) = ciborium::from_reader(sd_cwt.as_bytes()).unwrap()
+        else {
+            panic!("not a COSE_Sign1")
+        };
+        let CborValue::Array(items) = *cose_sign1 else {
+            panic!("not a COSE_Sign1")
+        };
+        vec![items[0].clone(), items[2].clone(), items[3].clone()]
+    }
+
+    #[test]
+    fn should_list_disclosures() {
+        let (_, sd_cwt) = issue();
+        let paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/given_name", "/family_name", "/email"]);
+    }
+
+    #[test]
+    fn should_present_by_path() {
+        let (issuer, sd_cwt) = issue();
+        let presentation = sd_cwt
+            .present(&["/given_name", "/email"].map(|p| p.parse().unwrap()))
+            .unwrap();
+
+        assert_eq!(signed_parts(&sd_cwt), signed_parts(&presentation));
+        assert_eq!(presentation.disclosures().unwrap().len(), 2);
+
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert!(has_claim(&payload, "given_name"));
+        assert!(has_claim(&payload, "email"));
+        assert!(has_claim(&payload, "phone_number"));
+        assert!(!has_claim(&payload, "family_name"));
+    }
+
+    #[test]
+    fn should_present_with_predicate() {
+        let (issuer, sd_cwt) = issue();
+        let presentation = sd_cwt
+            .present_with(|d| d.path.to_string().ends_with("_name"))
+            .unwrap();
+
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert!(has_claim(&payload, "given_name"));
+        assert!(has_claim(&payload, "family_name"));
+        assert!(!has_claim(&payload, "email"));
+    }
+
+    #[test]
+    fn should_present_nothing() {
+        let (issuer, sd_cwt) = issue();
+        let presentation = sd_cwt.present_with(|_| false).unwrap();
+
+        assert!(presentation.disclosures().unwrap().is_empty());
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert!(!has_claim(&payload, "given_name"));
+        assert!(has_claim(&payload, "phone_number"));
+    }
+
+    #[test]
+    fn should_present_array_elements() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            nationalities:
+                - !sd US
+                - !sd DE
+                - FR
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/nationalities/0", "/nationalities/1"]);
+
+        let presentation = sd_cwt
+            .present(&["/nationalities/1"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        let paths = presentation
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/nationalities/1"]);
+        let presentation = presentation
+            .present(&["/nationalities/1"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        let nationalities = payload
+            .custom
+            .get(&Keyy::new_text("nationalities".to_string()));
+        assert_eq!(
+            nationalities,
+            Some(&Anyy::new_array(vec![
+                Anyy::new_text("DE".to_string()),
+                Anyy::new_text("FR".to_string())
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_index_decoy_elements() {
+        let decoys = Decoys::PerMap([(ClaimPath::root().join("nationalities"), 5)].into());
+        let issuer = IssuerPrivateKey::generate().with_decoys(decoys);
+        let yaml = r#"
+            3: https://verifier.example
+            nationalities:
+                - !sd US
+                - FR
+                - !sd DE
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let disclosures = sd_cwt.disclosures().unwrap();
+        assert_eq!(disclosures.len(), 2);
+        let de = [disclosures[1].path.clone()];
+        assert!(de[0].starts_with(&ClaimPath::root().join("nationalities")));
+
+        // paths do not depend on the disclosures kept in a presentation
+        let presentation = sd_cwt.present(&de).unwrap();
+        let paths = presentation
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, de);
+        let presentation = presentation.present(&de).unwrap();
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert_eq!(
+            payload
+                .custom
+                .get(&Keyy::new_text("nationalities".to_string())),
+            Some(&Anyy::new_array(vec![
+                Anyy::new_text("FR".to_string()),
+                Anyy::new_text("DE".to_string())
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_present_nested_claims() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd address:
+                !sd street_address: "123 Main St"
+                country: US
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let mut paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["/address", "/address/street_address"]);
+
+        // the enclosing address is revealed along with the street address
+        let presentation = sd_cwt
+            .present(&["/address/street_address"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        assert_eq!(presentation.disclosures().unwrap().len(), 2);
+
+        let presentation = sd_cwt
+            .present(&["/address"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        let address = payload.custom.get(&Keyy::new_text("address".to_string()));
+        assert_eq!(
+            address,
+            Some(&Anyy::new_map(vec![(
+                Anyy::new_text("country".to_string()),
+                Anyy::new_text("US".to_string())
+            )]))
+        );
+    }
+
+    #[test]
+    fn should_escape_keys_with_slash() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd "a/b": 1
+            a:
+                !sd b: 2
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/a/b", "/a~1b"]);
+
+        let presentation = sd_cwt.present(&["/a~1b".parse().unwrap()]).unwrap();
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        let path = |p: &str| p.parse::<ClaimPath>().unwrap();
+        assert_eq!(
+            path("/a~1b").lookup(&payload),
+            Some(&Anyy::new_int(1.into_int()))
+        );
+        assert_eq!(path("/a/b").lookup(&payload), None);
+    }
+
+    #[test]
+    fn should_tell_numeric_text_keys_from_integer_keys() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd 500: integer
+            !sd "500": text
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let mut paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["/500", "/~2500"]);
+
+        let presentation = sd_cwt.present(&["/~2500".parse().unwrap()]).unwrap();
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        let path = |p: &str| p.parse::<ClaimPath>().unwrap();
+        assert_eq!(
+            path("/~2500").lookup(&payload),
+            Some(&Anyy::new_text("text".to_string()))
+        );
+        assert_eq!(path("/500").lookup(&payload), None);
+    }
+
+    #[test]
+    fn should_fail_presenting_unknown_path() {
+        let (_, sd_cwt) = issue();
+        assert!(matches!(
+            sd_cwt.present(&["/phone_number"].map(|p| p.parse().unwrap())),
+            Err(SdCwtError::UnknownClaimPath(p)) if p == "/phone_number"
+        ));
+    }
+}
@@ -1,16 +1,78 @@
-use std::collections::VecDeque;
+use std::collections::HashSet;
 use std::str::FromStr;
 
-use itertools::Itertools;
+use ciborium::Value as CborValue;
 use serde::{Deserialize, Deserializer};
-use serde_yaml::Value as YamlValue;
+use serde_yaml::{value::Tag, Mapping, Value as YamlValue};
 
-use crate::error::SdCwtError;
+use crate::cbor::yaml_to_cbor;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::labels::ClaimLabels;
+use crate::path::{ClaimPath, PathSegment};
 
+/// Claims set written in YAML where every disclosable claim or array element is tagged with `!sd`
 #[derive(Debug, Clone)]
 pub struct InputClaims {
+    /// Claims set as written, tags included
     pub raw: YamlValue,
-    pub disclosable_paths: Vec<String>,
+    /// JSON pointers of the disclosable claims and array elements, parents before their children
+    pub disclosable_paths: Vec<ClaimPath>,
+}
+
+impl InputClaims {
+    /// Claims set without its tags, as it is signed
+    pub fn to_cbor(&self) -> SdCwtResult<CborValue> {
+        yaml_to_cbor(&self.raw)
+    }
+
+    /// Claims set without its tags, with integer keys turned into strings
+    pub fn to_json(&self) -> SdCwtResult<serde_json::Value> {
+        Ok(serde_json::to_value(strip_tags(&self.raw))?)
+    }
+
+    /// Replaces the top-level claim names known to `labels` with their CWT label e.g. `iss` with
+    /// `1`, in the claims set as well as in the disclosable paths
+    pub fn with_labels(self, labels: &ClaimLabels) -> SdCwtResult<Self> {
+        let YamlValue::Mapping(claims) = self.raw else {
+            return Err(SdCwtError::InvalidClaimsSet);
+        };
+        let mut raw = Mapping::with_capacity(claims.len());
+        let mut keys = HashSet::with_capacity(claims.len());
+        for (key, value) in claims {
+            let key = match key {
+                YamlValue::Tagged(mut tagged) => {
+                    tagged.value = relabel_key(tagged.value, labels);
+                    YamlValue::Tagged(tagged)
+                }
+                key => relabel_key(key, labels),
+            };
+            let segment = key_segment(untagged(&key));
+            if !keys.insert(segment.clone()) {
+                let name = segment.map(|s| s.to_string()).unwrap_or_default();
+                return Err(SdCwtError::DuplicateClaim(name));
+            }
+            raw.insert(key, value);
+        }
+
+        let disclosable_paths = self
+            .disclosable_paths
+            .into_iter()
+            .map(|path| {
+                let mut segments = path.segments().to_vec();
+                if let Some(PathSegment::Text(name)) = segments.first() {
+                    if let Some(label) = labels.label(name) {
+                        segments[0] = PathSegment::Int(label);
+                    }
+                }
+                ClaimPath::from(segments)
+            })
+            .collect();
+
+        Ok(Self {
+            raw: YamlValue::Mapping(raw),
+            disclosable_paths,
+        })
+    }
 }
 
 impl FromStr for InputClaims {
@@ -26,11 +88,18 @@ impl<'de> Deserialize<'de> for InputClaims {
     where
         D: Deserializer<'de>,
     {
-        let mut value = YamlValue::deserialize(deserializer)?;
-        let mut path = Vec::new();
+        let value = YamlValue::deserialize(deserializer)?;
+        Self::from_tagged_yaml(value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl InputClaims {
+    /// Collects the paths of the claims and array elements tagged with `!sd`
+    pub(crate) fn from_tagged_yaml(mut value: YamlValue) -> SdCwtResult<Self> {
+        let mut path = ClaimPath::root();
         let mut tagged_paths = Vec::new();
         collect_tagged_keys(&mut value, &mut path, &mut tagged_paths).map_err(|e| {
-            serde::de::Error::custom(format!("Error parsing YAML at path {:?}: {:?}", path, e))
+            SdCwtError::InvalidInput(format!("Error parsing YAML at path {}: {:?}", path, e))
         })?;
 
         Ok(Self {
@@ -40,12 +109,17 @@ impl<'de> Deserialize<'de> for InputClaims {
     }
 }
 
+/// Tag marking a disclosable claim or array element
+pub(crate) fn sd_tag() -> Tag {
+    Tag::new("!sd")
+}
+
 fn collect_tagged_keys(
     node: &mut YamlValue,
-    path: &mut Vec<String>,
-    paths: &mut Vec<String>,
+    path: &mut ClaimPath,
+    paths: &mut Vec<ClaimPath>,
 ) -> Result<(), SdCwtError> {
-    let sd_tag = serde_yaml::value::Tag::new("!sd");
+    let sd_tag = sd_tag();
     match node {
         YamlValue::Mapping(obj) => {
             for (key, value) in obj {
@@ -53,54 +127,43 @@ fn collect_tagged_keys(
                     YamlValue::Tagged(tag) => {
                         let tag = tag.as_ref();
                         if tag.tag == sd_tag {
-                            let new_val = tag
-                                .value
-                                .as_str()
+                            let segment = key_segment(&tag.value)
                                 .ok_or_else(|| SdCwtError::InvalidYamlInput(tag.tag.clone()))?;
-                            let full_path = build_full_path(path, new_val);
-                            paths.push(full_path);
+                            paths.push(path.join(segment.clone()));
+                            // the disclosed value might have disclosable claims of its own
+                            path.push(segment);
+                            collect_tagged_keys(value, path, paths)?;
+                            path.pop();
                         }
                     }
-                    YamlValue::String(key) => {
-                        path.push(key.to_string());
-                        collect_tagged_keys(value, path, paths)?;
-                        path.pop();
+                    key => {
+                        if let Some(key) = key_segment(key) {
+                            path.push(key);
+                            collect_tagged_keys(value, path, paths)?;
+                            path.pop();
+                        }
                     }
-                    _ => {}
                 }
             }
         }
         YamlValue::Sequence(seq) => {
             for (index, value) in seq.iter_mut().enumerate() {
-                path.push(index.to_string());
+                path.push(index);
                 collect_tagged_keys(value, path, paths)?;
                 // Ugly hack to remove tag from sequence
                 if let YamlValue::Tagged(tag) = &value {
-                    let tag = tag.as_ref();
                     if tag.tag == sd_tag {
-                        let new_val = tag
-                            .value
-                            .as_str()
-                            .ok_or_else(|| SdCwtError::InvalidYamlInput(tag.tag.clone()))?;
-                        *value = YamlValue::String(new_val.to_string());
+                        *value = tag.value.clone();
                     }
                 }
                 path.pop();
             }
         }
         YamlValue::Tagged(tag) => {
-            let tag = tag.as_ref();
-            if tag.tag == sd_tag {
-                let mut full_path = String::new();
-                for (index, path_fragment) in path.iter().enumerate() {
-                    if index == 0 {
-                        full_path = format!("/{}", path_fragment);
-                    } else {
-                        full_path = format!("{}/{}", full_path, path_fragment)
-                    }
-                }
-                paths.push(full_path);
+            if tag.tag == sd_tag && !path.is_root() {
+                paths.push(path.clone());
             }
+            collect_tagged_keys(&mut tag.value, path, paths)?;
         }
         _ => {}
     }
@@ -108,9 +171,40 @@ fn collect_tagged_keys(
     Ok(())
 }
 
-fn build_full_path(path: &Vec<String>, additional_segment: &str) -> String {
-    let full_path = path.iter().join("/");
-    format!("{}/{}", full_path, additional_segment)
+fn relabel_key(key: YamlValue, labels: &ClaimLabels) -> YamlValue {
+    match key.as_str().and_then(|name| labels.label(name)) {
+        Some(label) => YamlValue::Number(label.into()),
+        None => key,
+    }
+}
+
+pub(crate) fn untagged(value: &YamlValue) -> &YamlValue {
+    match value {
+        YamlValue::Tagged(tagged) => untagged(&tagged.value),
+        value => value,
+    }
+}
+
+/// Path segment of a text or integer (e.g. a CWT label) map key
+fn key_segment(key: &YamlValue) -> Option<PathSegment> {
+    match key {
+        YamlValue::String(key) => Some(PathSegment::Text(key.clone())),
+        YamlValue::Number(n) => n.as_i64().map(PathSegment::Int),
+        _ => None,
+    }
+}
+
+fn strip_tags(value: &YamlValue) -> YamlValue {
+    match value {
+        YamlValue::Tagged(tagged) => strip_tags(&tagged.value),
+        YamlValue::Sequence(seq) => YamlValue::Sequence(seq.iter().map(strip_tags).collect()),
+        YamlValue::Mapping(map) => YamlValue::Mapping(
+            map.iter()
+                .map(|(k, v)| (strip_tags(k), strip_tags(v)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
 }
 
 #[cfg(test)]
@@ -126,23 +220,42 @@ mod tests {
 
         let input = yaml.parse::<InputClaims>().unwrap();
 
-        println!("{:?}", input.raw);
-        println!("{:?}", input.disclosable_paths);
-        
-        /*assert_eq!(
-            json,
+        assert_eq!(
+            input.to_json().unwrap(),
             serde_json::json!({
                 "sub": "user_42",
                 "given_name": "John",
             })
         );
+        assert_eq!(input.disclosable_paths, vec!["/given_name"]);
+    }
 
-        assert_eq!(tagged_paths, vec!["/given_name",]);*/
+    #[test]
+    fn should_collect_nested_paths() {
+        let yaml = r#"
+            !sd address:
+                !sd street_address: "123 Main St"
+                country: US
+            nationalities:
+                - !sd
+                    !sd code: US
+        "#;
+
+        let input = yaml.parse::<InputClaims>().unwrap();
+        assert_eq!(
+            input.disclosable_paths,
+            vec![
+                "/address",
+                "/address/street_address",
+                "/nationalities/0",
+                "/nationalities/0/code",
+            ]
+        );
     }
 
-    /*#[test]
-    fn test_parse_yaml1() {
-        let yaml_str = r#"
+    #[test]
+    fn should_parse_flat_and_nested_keys() {
+        let yaml = r#"
             sub: user_42
             !sd given_name: John
             !sd family_name: Doe
@@ -161,12 +274,10 @@ mod tests {
                 - DE
             "#;
 
-        let (json, tagged_paths) = crate::parser::parse_yaml(yaml_str).unwrap();
-        println!("{:?}", json);
-        println!("{:?}", tagged_paths);
+        let input = yaml.parse::<InputClaims>().unwrap();
 
         assert_eq!(
-            json,
+            input.to_json().unwrap(),
             serde_json::json!({
                 "sub": "user_42",
                 "given_name": "John",
@@ -190,7 +301,7 @@ mod tests {
         );
 
         assert_eq!(
-            tagged_paths,
+            input.disclosable_paths,
             vec![
                 "/given_name",
                 "/family_name",
@@ -202,8 +313,8 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_yaml2() {
-        let yaml_str = r#"
+    fn should_parse_whole_map_and_array_elements() {
+        let yaml = r#"
             sub: user_42
             !sd given_name: John
             !sd family_name: Doe
@@ -223,12 +334,10 @@ mod tests {
                 - PL
             "#;
 
-        let (json, tagged_paths) = crate::parser::parse_yaml(yaml_str).unwrap();
-        println!("{:?}", json);
-        println!("{:?}", tagged_paths);
+        let input = yaml.parse::<InputClaims>().unwrap();
 
         assert_eq!(
-            json,
+            input.to_json().unwrap(),
             serde_json::json!({
                 "sub": "user_42",
                 "given_name": "John",
@@ -253,7 +362,7 @@ mod tests {
         );
 
         assert_eq!(
-            tagged_paths,
+            input.disclosable_paths,
             vec![
                 "/given_name",
                 "/family_name",
@@ -262,5 +371,161 @@ mod tests {
                 "/nationalities/1",
             ]
         );
-    }*/
+    }
+
+    #[test]
+    fn should_keep_integer_keys() {
+        let yaml = r#"
+            1: https://issuer.example
+            6: 1725244200
+            !sd given_name: John
+        "#;
+
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        let CborValue::Map(entries) = input.to_cbor().unwrap() else {
+            panic!("claims set should be a map")
+        };
+        assert_eq!(entries[0].0, CborValue::from(1));
+        assert_eq!(entries[1].0, CborValue::from(6));
+        assert_eq!(
+            input.to_json().unwrap(),
+            serde_json::json!({
+                "1": "https://issuer.example",
+                "6": 1725244200,
+                "given_name": "John",
+            })
+        );
+        assert_eq!(input.disclosable_paths, vec!["/given_name"]);
+    }
+
+    #[test]
+    fn should_collect_integer_keys() {
+        let yaml = r#"
+            !sd 500: private
+            1000:
+                !sd -1: x
+                2:
+                    - !sd y
+        "#;
+
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        assert_eq!(
+            input.disclosable_paths,
+            vec!["/500", "/1000/-1", "/1000/2/0"]
+        );
+    }
+
+    #[test]
+    fn should_relabel_registered_claims() {
+        let yaml = r#"
+            iss: https://issuer.example
+            aud: https://verifier.example
+            !sd sub: user_42
+            !sd tenant:
+                !sd region: eu
+        "#;
+        let labels = ClaimLabels::default().with_label("tenant", -65538);
+
+        let input = yaml
+            .parse::<InputClaims>()
+            .unwrap()
+            .with_labels(&labels)
+            .unwrap();
+
+        assert_eq!(
+            input.to_json().unwrap(),
+            serde_json::json!({
+                "1": "https://issuer.example",
+                "3": "https://verifier.example",
+                "2": "user_42",
+                "-65538": { "region": "eu" },
+            })
+        );
+        assert_eq!(
+            input.disclosable_paths,
+            vec!["/2", "/-65538", "/-65538/region"]
+        );
+    }
+
+    #[test]
+    fn should_fail_relabelling_to_existing_claim() {
+        let yaml = r#"
+            iss: https://issuer.example
+            1: https://other.example
+        "#;
+        let input = yaml.parse::<InputClaims>().unwrap();
+        assert!(matches!(
+            input.with_labels(&ClaimLabels::default()),
+            Err(SdCwtError::DuplicateClaim(c)) if c == "1"
+        ));
+    }
+
+    #[test]
+    fn should_parse_nested_sequences() {
+        let yaml = r#"
+            matrix:
+                - - !sd a
+                  - b
+                - - c
+                  - !sd d
+        "#;
+
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        assert_eq!(
+            input.to_json().unwrap(),
+            serde_json::json!({ "matrix": [["a", "b"], ["c", "d"]] })
+        );
+        assert_eq!(input.disclosable_paths, vec!["/matrix/0/0", "/matrix/1/1"]);
+    }
+
+    #[test]
+    fn should_parse_tags_on_non_string_values() {
+        let yaml = r#"
+            !sd age: 42
+            !sd verified: true
+            scores:
+                - !sd 12
+                - !sd 3.5
+                - !sd null
+                - !sd [1, 2]
+                - !sd { a: b }
+            name: !sd John
+        "#;
+
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        assert_eq!(
+            input.to_json().unwrap(),
+            serde_json::json!({
+                "age": 42,
+                "verified": true,
+                "scores": [12, 3.5, null, [1, 2], { "a": "b" }],
+                "name": "John",
+            })
+        );
+        assert_eq!(
+            input.disclosable_paths,
+            vec![
+                "/age",
+                "/verified",
+                "/scores/0",
+                "/scores/1",
+                "/scores/2",
+                "/scores/3",
+                "/scores/4",
+                "/name",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_with_sd_tag_on_non_scalar_key() {
+        let yaml = r#"
+            !sd [a, b]: c
+        "#;
+        assert!(yaml.parse::<InputClaims>().is_err());
+    }
 }
@@ -0,0 +1,816 @@
+use crate::cbor::{sig_structure, IntoInt};
+use crate::cose_key::{Confirmation, CoseKey};
+use crate::decoy::{decoy_digest, insert_shuffled, Decoys};
+use crate::edn::EdnClaims;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::hash::HashAlgorithm;
+use crate::input::InputClaims;
+use crate::path::{ClaimPath, PathSegment};
+use crate::signing::{SigningAlgorithm, SigningKey};
+use crate::types;
+use crate::verifier::VerifierPublicKey;
+use crate::SdCwt;
+use ciborium::Value as CborValue;
+use itertools::Itertools;
+use sd_cwt_types::{
+    ordered_hash_map::OrderedHashMap, serialization::ToCBORBytes, Anyy, Int, IntOrText, Keyy,
+    Salted, SaltedClaimItem, SaltedElementItem, SdPayload, SdProtected, Unprotected,
+};
+
+/// Content type of an SD-CWT
+pub const SD_CWT_TYP: &str = "application/sd+cwt";
+/// Label of the unprotected header holding the disclosures
+pub const SD_CLAIMS_LABEL: i64 = 1111;
+/// Label of the unprotected header holding the key binding token
+pub const SD_KBT_LABEL: i64 = 1112;
+/// Label of the confirmation claim
+pub const CNF_LABEL: i64 = 8;
+/// Label of the claim advertising the hash algorithm of the disclosures digests
+pub const SD_ALG_LABEL: i64 = 1114;
+/// Label of the claim holding the digests of the redacted keys of a map
+pub const REDACTED_KEYS_LABEL: i64 = 1115;
+/// Key of the single entry of a redacted array element, holding the digest of its disclosure
+pub const REDACTED_ELEMENT_KEY: &str = "...";
+
+/// Key of the issuer, signing SD-CWTs with the `alg` matching its [SigningAlgorithm]
+pub struct IssuerPrivateKey {
+    key: SigningKey,
+    decoys: Decoys,
+}
+
+impl IssuerPrivateKey {
+    pub fn new(key: impl Into<SigningKey>) -> Self {
+        Self {
+            key: key.into(),
+            decoys: Decoys::default(),
+        }
+    }
+
+    /// Generates an EdDSA key
+    pub fn generate() -> Self {
+        Self::generate_with_algorithm(SigningAlgorithm::EdDsa)
+    }
+
+    pub fn generate_with_algorithm(alg: SigningAlgorithm) -> Self {
+        Self::new(SigningKey::generate(alg))
+    }
+
+    /// Mixes decoy digests with the ones of the redacted claims in every SD-CWT issued from now on
+    pub fn with_decoys(mut self, decoys: Decoys) -> Self {
+        self.decoys = decoys;
+        self
+    }
+
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        self.key.algorithm()
+    }
+
+    pub fn public_key(&self) -> VerifierPublicKey {
+        self.key.public_key()
+    }
+
+    /// Salts & hashes every disclosable claim then signs the redacted claims set as a COSE_Sign1.
+    /// Standard claims have to be provided with their CWT label (e.g. `3` for `aud`). When absent,
+    /// `iat` is set to the current time. Disclosures are hashed with the algorithm set in `sd_alg`
+    /// (`1114`), SHA-256 by default.
+    pub fn sign(&self, claims: InputClaims) -> SdCwtResult<SdCwt> {
+        let claims_set = claims.to_cbor()?;
+        self.sign_claims_set(claims_set, &claims.disclosable_paths)
+    }
+
+    /// Same as [Self::sign] for a claims set written in CBOR diagnostic notation
+    pub fn sign_edn(&self, claims: EdnClaims) -> SdCwtResult<SdCwt> {
+        self.sign_claims_set(claims.to_cbor(), &claims.disclosable_paths)
+    }
+
+    /// Same as [Self::sign] but also confirms the holder key in the `cnf` claim so that the holder
+    /// can later bind its presentations with a key binding token
+    pub fn sign_with_holder_key(
+        &self,
+        claims: InputClaims,
+        holder_key: &VerifierPublicKey,
+    ) -> SdCwtResult<SdCwt> {
+        let mut claims_set = claims.to_cbor()?;
+        let entries = claims_set
+            .as_map_mut()
+            .ok_or(SdCwtError::InvalidClaimsSet)?;
+        let cnf_label = CborValue::from(CNF_LABEL);
+        entries.retain(|(k, _)| *k != cnf_label);
+        let cnf = Confirmation::CoseKey(CoseKey::from(holder_key));
+        entries.push((cnf_label, cnf.to_cbor()));
+        self.sign_claims_set(claims_set, &claims.disclosable_paths)
+    }
+
+    fn sign_claims_set(
+        &self,
+        mut claims_set: CborValue,
+        paths: &[ClaimPath],
+    ) -> SdCwtResult<SdCwt> {
+        let hash_alg = hash_algorithm(&claims_set)?;
+        let mut disclosures = vec![];
+        let (mut redacted_keys, array_decoys) =
+            add_decoys(&mut claims_set, paths, &self.decoys, hash_alg)?;
+        redact(
+            &mut claims_set,
+            paths,
+            hash_alg,
+            array_decoys,
+            &mut redacted_keys,
+            &mut disclosures,
+        )?;
+
+        let mut payload = into_sd_payload(claims_set)?;
+        payload.sd_alg = Some(hash_alg.cose_alg().into_int());
+        payload.redacted_keys = Some(redacted_keys).filter(|k| !k.is_empty());
+
+        let mut unprotected = Unprotected::new(OrderedHashMap::new());
+        unprotected.sd_claims = Some(disclosures).filter(|d| !d.is_empty());
+
+        let sd_cwt = sign_cose_sign1(&self.key, SD_CWT_TYP, unprotected, payload)?;
+        Ok(SdCwt(sd_cwt))
+    }
+}
+
+/// Signs the payload as a tagged COSE_Sign1 and returns its encoding. The `alg` protected header
+/// is the one of the signing key.
+pub(crate) fn sign_cose_sign1(
+    key: &SigningKey,
+    typ: &str,
+    unprotected: Unprotected,
+    payload: SdPayload,
+) -> SdCwtResult<Vec<u8>> {
+    let protected = SdProtected::builder()
+        .alg(key.algorithm().cose_alg())
+        .typ(typ)
+        .build()?;
+
+    let tbs = sig_structure(&protected.to_cbor_bytes(), &payload.to_cbor_bytes())?;
+    let signature = key.sign(&tbs);
+
+    let sd_cwt = types::SdCwt::new(protected, unprotected, payload, signature);
+    Ok(sd_cwt.to_cbor_bytes())
+}
+
+/// Removes every claim designated by `paths` from the claims set and replaces it with the digest
+/// of its salted disclosure. Digests of top-level claims are added to `redacted_keys` whereas the
+/// ones of nested claims are stored in the `redacted_keys` of the map they belonged to. Array
+/// elements are replaced in place by a redacted element i.e. `{ "...": digest }`.
+fn redact(
+    claims_set: &mut CborValue,
+    paths: &[ClaimPath],
+    hash_alg: HashAlgorithm,
+    mut array_decoys: ArrayDecoys,
+    redacted_keys: &mut Vec<Vec<u8>>,
+    disclosures: &mut Vec<Salted>,
+) -> SdCwtResult<()> {
+    // redact the deepest claims first so that their digests end up in their (maybe redacted) parent
+    let paths = paths
+        .iter()
+        .sorted_by_key(|path| std::cmp::Reverse(path.len()));
+
+    for path in paths {
+        // decoy elements shift the indices, so they are added once no remaining path goes through
+        // their array
+        insert_decoy_elements(claims_set, &mut array_decoys, path.len(), hash_alg)?;
+
+        let invalid_path = || SdCwtError::InvalidDisclosablePath(path.clone());
+        let (name, parents) = path.segments().split_last().ok_or_else(invalid_path)?;
+        let salt = rand::random::<[u8; 16]>().to_vec();
+
+        match lookup_mut(claims_set, parents).ok_or_else(invalid_path)? {
+            CborValue::Map(entries) => {
+                let position = entries
+                    .iter()
+                    .position(|(k, _)| name.is_key(k))
+                    .ok_or_else(invalid_path)?;
+                let (key, value) = entries.remove(position);
+                let name = match key {
+                    CborValue::Integer(i) => Int::try_from(i128::from(i))
+                        .map(IntOrText::Int)
+                        .map_err(|_| SdCwtError::InvalidClaimKey)?,
+                    CborValue::Text(text) => IntOrText::new_text(text),
+                    _ => return Err(SdCwtError::InvalidClaimKey),
+                };
+
+                let salted_claim = SaltedClaimItem::new(salt, name, Anyy::from(value))?;
+                let disclosure = Salted::new_salted_claim(salted_claim);
+                let digest = hash_alg.digest(disclosure.to_cbor_bytes());
+                disclosures.push(disclosure);
+
+                if parents.is_empty() {
+                    insert_shuffled(redacted_keys, digest);
+                } else {
+                    push_redacted_key(entries, digest)?;
+                }
+            }
+            CborValue::Array(elements) => {
+                let element = name
+                    .index()
+                    .and_then(|i| elements.get_mut(i))
+                    .ok_or_else(invalid_path)?;
+                let value = std::mem::replace(element, CborValue::Null);
+
+                let salted_element = SaltedElementItem::new(salt, Anyy::from(value))?;
+                let disclosure = Salted::new_salted_element(salted_element);
+                let digest = hash_alg.digest(disclosure.to_cbor_bytes());
+                disclosures.push(disclosure);
+
+                *element = redacted_element(digest);
+            }
+            _ => return Err(invalid_path()),
+        }
+    }
+    insert_decoy_elements(claims_set, &mut array_decoys, 0, hash_alg)
+}
+
+/// Number of decoy elements still to be inserted in each array, designated by its path
+type ArrayDecoys = Vec<(ClaimPath, usize)>;
+
+/// Adds decoy digests to the maps and arrays designated by `decoys` and to the ones with redacted
+/// claims. Those of the claims set are returned whereas the other ones are stored in the
+/// `redacted_keys` of their map. Since decoy elements would shift the indices of the disclosable
+/// paths, the decoys of arrays are only counted and returned.
+fn add_decoys(
+    claims_set: &mut CborValue,
+    paths: &[ClaimPath],
+    decoys: &Decoys,
+    hash_alg: HashAlgorithm,
+) -> SdCwtResult<(Vec<Vec<u8>>, ArrayDecoys)> {
+    let parents = paths.iter().filter_map(ClaimPath::parent);
+    let map_paths = decoys.map_paths().into_iter().chain(parents).unique();
+
+    let (mut redacted_keys, mut array_decoys) = (vec![], vec![]);
+    for map_path in map_paths {
+        let count = decoys.count(&map_path);
+        if count == 0 {
+            continue;
+        }
+        let digests = (0..count).map(|_| decoy_digest(hash_alg));
+        if map_path.is_root() {
+            redacted_keys.extend(digests);
+            continue;
+        }
+        match lookup_mut(claims_set, map_path.segments()) {
+            Some(CborValue::Map(entries)) => {
+                for digest in digests {
+                    push_redacted_key(entries, digest)?;
+                }
+            }
+            Some(CborValue::Array(_)) => array_decoys.push((map_path, count)),
+            _ => return Err(SdCwtError::InvalidDisclosablePath(map_path)),
+        }
+    }
+    Ok((redacted_keys, array_decoys))
+}
+
+/// Inserts, at random positions, the decoy elements of the arrays at least as deep as `depth`
+fn insert_decoy_elements(
+    claims_set: &mut CborValue,
+    array_decoys: &mut ArrayDecoys,
+    depth: usize,
+    hash_alg: HashAlgorithm,
+) -> SdCwtResult<()> {
+    let (ready, pending) = array_decoys
+        .drain(..)
+        .partition::<Vec<_>, _>(|(path, _)| path.len() >= depth);
+    *array_decoys = pending;
+    for (path, count) in ready {
+        let Some(CborValue::Array(elements)) = lookup_mut(claims_set, path.segments()) else {
+            return Err(SdCwtError::InvalidDisclosablePath(path));
+        };
+        for _ in 0..count {
+            insert_shuffled(elements, redacted_element(decoy_digest(hash_alg)));
+        }
+    }
+    Ok(())
+}
+
+/// Placeholder of a redacted array element
+fn redacted_element(digest: Vec<u8>) -> CborValue {
+    CborValue::Map(vec![(
+        REDACTED_ELEMENT_KEY.into(),
+        CborValue::Bytes(digest),
+    )])
+}
+
+/// Digest carried by a redacted array element, if `value` is one
+pub(crate) fn redacted_element_digest(value: &Anyy) -> Option<&[u8]> {
+    let Anyy::Map { entries, .. } = value else {
+        return None;
+    };
+    match entries.as_slice() {
+        [(Anyy::Text { text, .. }, Anyy::Bytes { bytes, .. })] if text == REDACTED_ELEMENT_KEY => {
+            Some(bytes)
+        }
+        _ => None,
+    }
+}
+
+/// Claim, map entry or array element designated by the path segments
+fn lookup_mut<'a>(
+    claims_set: &'a mut CborValue,
+    segments: &[PathSegment],
+) -> Option<&'a mut CborValue> {
+    segments
+        .iter()
+        .try_fold(claims_set, |parent, segment| match parent {
+            CborValue::Map(entries) => entries
+                .iter_mut()
+                .find_map(|(k, v)| segment.is_key(k).then_some(v)),
+            CborValue::Array(elements) => segment.index().and_then(|i| elements.get_mut(i)),
+            _ => None,
+        })
+}
+
+/// Adds a digest to the `redacted_keys` of a nested map
+fn push_redacted_key(
+    entries: &mut Vec<(CborValue, CborValue)>,
+    digest: Vec<u8>,
+) -> SdCwtResult<()> {
+    let redacted_keys_label = CborValue::from(REDACTED_KEYS_LABEL);
+    match entries.iter_mut().find(|(k, _)| *k == redacted_keys_label) {
+        Some((_, CborValue::Array(digests))) => insert_shuffled(digests, CborValue::Bytes(digest)),
+        Some(_) => return Err(SdCwtError::InvalidClaim(REDACTED_KEYS_LABEL)),
+        None => entries.push((
+            redacted_keys_label,
+            CborValue::Array(vec![CborValue::Bytes(digest)]),
+        )),
+    }
+    Ok(())
+}
+
+/// Hash algorithm requested in the `sd_alg` claim of the claims set
+fn hash_algorithm(claims_set: &CborValue) -> SdCwtResult<HashAlgorithm> {
+    let sd_alg_label = CborValue::from(SD_ALG_LABEL);
+    let sd_alg = claims_set
+        .as_map()
+        .and_then(|entries| entries.iter().find(|(k, _)| *k == sd_alg_label));
+    match sd_alg {
+        Some((_, alg)) => {
+            let alg = alg
+                .as_integer()
+                .and_then(|i| i64::try_from(i).ok())
+                .ok_or(SdCwtError::InvalidClaim(SD_ALG_LABEL))?;
+            HashAlgorithm::try_from(alg)
+        }
+        None => Ok(HashAlgorithm::default()),
+    }
+}
+
+/// Maps the registered claims onto the [SdPayload] fields and collects all the other ones as custom claims.
+/// Labels reserved by SD-CWT, but `sd_alg`, are rejected rather than set twice.
+fn into_sd_payload(claims_set: CborValue) -> SdCwtResult<SdPayload> {
+    let CborValue::Map(entries) = claims_set else {
+        return Err(SdCwtError::InvalidClaimsSet);
+    };
+
+    let (mut iss, mut sub, mut aud, mut exp, mut nbf, mut iat) =
+        (None, None, None, None, None, None);
+    let (mut cnonce, mut cnf) = (None, None);
+    let mut custom = OrderedHashMap::new();
+    for (key, value) in entries {
+        let label = key.as_integer().and_then(|i| i64::try_from(i).ok());
+        match label {
+            Some(1) => iss = Some(text_claim(value, 1)?),
+            Some(2) => sub = Some(text_claim(value, 2)?),
+            Some(3) => aud = Some(text_claim(value, 3)?),
+            Some(4) => exp = Some(int_claim(value, 4)?),
+            Some(5) => nbf = Some(int_claim(value, 5)?),
+            Some(6) => iat = Some(int_claim(value, 6)?),
+            Some(39) => {
+                cnonce = Some(
+                    value
+                        .into_bytes()
+                        .map_err(|_| SdCwtError::InvalidClaim(39))?,
+                )
+            }
+            Some(8) => {
+                let entries = value.into_map().map_err(|_| SdCwtError::InvalidClaim(8))?;
+                let mut cnf_map = OrderedHashMap::new();
+                for (k, v) in entries {
+                    cnf_map.insert(keyy(k)?, Anyy::from(v));
+                }
+                cnf = Some(cnf_map);
+            }
+            // set from the hash algorithm actually used
+            Some(SD_ALG_LABEL) => {}
+            Some(label @ SD_CLAIMS_LABEL..=REDACTED_KEYS_LABEL) => {
+                return Err(SdCwtError::ReservedClaim(label))
+            }
+            _ => {
+                custom.insert(keyy(key)?, Anyy::from(value));
+            }
+        }
+    }
+
+    let aud = aud.ok_or(SdCwtError::MissingClaim(3))?;
+    let iat = match iat {
+        Some(iat) => iat,
+        None => now()?.into_int(),
+    };
+
+    let mut payload = SdPayload::new(aud, iat, custom);
+    payload.iss = iss;
+    payload.sub = sub;
+    payload.exp = exp;
+    payload.nbf = nbf;
+    payload.cnonce = cnonce;
+    payload.cnf = cnf;
+    Ok(payload)
+}
+
+fn text_claim(value: CborValue, label: i64) -> SdCwtResult<String> {
+    value
+        .into_text()
+        .map_err(|_| SdCwtError::InvalidClaim(label))
+}
+
+fn int_claim(value: CborValue, label: i64) -> SdCwtResult<Int> {
+    value
+        .as_integer()
+        .and_then(|i| Int::try_from(i128::from(i)).ok())
+        .ok_or(SdCwtError::InvalidClaim(label))
+}
+
+fn keyy(key: CborValue) -> SdCwtResult<Keyy> {
+    match key {
+        CborValue::Integer(i) => Int::try_from(i128::from(i))
+            .map(Keyy::new_int)
+            .map_err(|_| SdCwtError::InvalidClaimKey),
+        CborValue::Text(text) => Ok(Keyy::new_text(text)),
+        _ => Err(SdCwtError::InvalidClaimKey),
+    }
+}
+
+pub(crate) fn now() -> SdCwtResult<i64> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| SdCwtError::InvalidClaim(6))?;
+    Ok(now.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use sd_cwt_types::serialization::Deserialize;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::hash::COSE_ALG_SHA256;
+    use crate::signing::COSE_ALG_EDDSA;
+
+    #[test]
+    fn should_sign() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            1: https://issuer.example
+            3: https://verifier.example
+            6: 1725244200
+            !sd given_name: John
+            !sd family_name: Doe
+            email: "johndoe@example.com"
+            address:
+                !sd street_address: "123 Main St"
+                country: US
+        "#;
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        let sd_cwt = issuer.sign(input).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        assert_eq!(sd_cwt.protected.alg, COSE_ALG_EDDSA.into_int());
+        assert_eq!(sd_cwt.protected.typ, SD_CWT_TYP);
+
+        let payload = &sd_cwt.payload;
+        assert_eq!(payload.iss.as_deref(), Some("https://issuer.example"));
+        assert_eq!(payload.aud, "https://verifier.example");
+        assert_eq!(payload.iat, 1725244200.into_int());
+        assert_eq!(payload.sd_alg, Some(COSE_ALG_SHA256.into_int()));
+        assert_eq!(
+            payload.custom.get(&Keyy::new_text("email".to_string())),
+            Some(&Anyy::new_text("johndoe@example.com".to_string()))
+        );
+        let Some(Anyy::Map { entries, .. }) =
+            payload.custom.get(&Keyy::new_text("address".to_string()))
+        else {
+            panic!("address should be a map")
+        };
+        assert_eq!(
+            entries[0],
+            (
+                Anyy::new_text("country".to_string()),
+                Anyy::new_text("US".to_string())
+            )
+        );
+        assert!(!payload
+            .custom
+            .contains_key(&Keyy::new_text("given_name".to_string())));
+
+        // the street address digest lives in the 'address' map
+        let redacted_keys = payload.redacted_keys.as_ref().unwrap();
+        assert_eq!(redacted_keys.len(), 2);
+        let disclosures = sd_cwt.unprotected.sd_claims.as_ref().unwrap();
+        assert_eq!(disclosures.len(), 3);
+
+        let digests = disclosures
+            .iter()
+            .map(|d| Sha256::digest(d.to_cbor_bytes()).to_vec())
+            .collect::<Vec<_>>();
+        assert!(redacted_keys.iter().all(|k| digests.contains(k)));
+    }
+
+    #[test]
+    fn should_add_decoys() {
+        let issuer = IssuerPrivateKey::generate().with_decoys(Decoys::Fixed(3));
+        let yaml = r#"
+            3: https://verifier.example
+            !sd given_name: John
+            address:
+                !sd street_address: "123 Main St"
+                country: US
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        assert_eq!(sd_cwt.payload.redacted_keys.as_ref().unwrap().len(), 4);
+        let Some(Anyy::Map { entries, .. }) = sd_cwt
+            .payload
+            .custom
+            .get(&Keyy::new_text("address".to_string()))
+        else {
+            panic!("address should be a map")
+        };
+        let redacted_keys = entries
+            .iter()
+            .find(|(k, _)| *k == Anyy::new_int(REDACTED_KEYS_LABEL.into_int()))
+            .map(|(_, v)| v);
+        let Some(Anyy::Array { elements, .. }) = redacted_keys else {
+            panic!("address should have redacted keys")
+        };
+        assert_eq!(elements.len(), 4);
+        assert!(elements
+            .iter()
+            .all(|e| matches!(e, Anyy::Bytes { bytes, .. } if bytes.len() == 32)));
+    }
+
+    #[test]
+    fn should_add_decoys_per_map() {
+        let decoys = Decoys::PerMap([(ClaimPath::root().join("address"), 2)].into());
+        let issuer = IssuerPrivateKey::generate().with_decoys(decoys);
+        let yaml = r#"
+            3: https://verifier.example
+            !sd given_name: John
+            address:
+                country: US
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        assert_eq!(sd_cwt.payload.redacted_keys.as_ref().unwrap().len(), 1);
+        let Some(Anyy::Map { entries, .. }) = sd_cwt
+            .payload
+            .custom
+            .get(&Keyy::new_text("address".to_string()))
+        else {
+            panic!("address should be a map")
+        };
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn should_fail_with_decoys_for_unknown_map() {
+        let decoys = Decoys::PerMap([(ClaimPath::root().join("address"), 2)].into());
+        let issuer = IssuerPrivateKey::generate().with_decoys(decoys);
+        let input = "3: https://verifier.example"
+            .parse::<InputClaims>()
+            .unwrap();
+        assert!(matches!(
+            issuer.sign(input),
+            Err(SdCwtError::InvalidDisclosablePath(p)) if p == "/address"
+        ));
+    }
+
+    #[test]
+    fn should_redact_integer_keys() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd 500: private
+            1000:
+                !sd -1: x
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let payload = issuer.public_key().verify(&sd_cwt).unwrap();
+
+        assert_eq!(
+            payload.custom.get(&Keyy::new_int(500.into_int())),
+            Some(&Anyy::new_text("private".to_string()))
+        );
+        assert_eq!(
+            payload.custom.get(&Keyy::new_int(1000.into_int())),
+            Some(&Anyy::new_map(vec![(
+                Anyy::new_int((-1).into_int()),
+                Anyy::new_text("x".to_string())
+            )]))
+        );
+        let paths = sd_cwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/500", "/1000/-1"]);
+    }
+
+    #[test]
+    fn should_default_iat() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "3: https://verifier.example"
+            .parse::<InputClaims>()
+            .unwrap();
+
+        let sd_cwt = issuer.sign(input).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        assert!(sd_cwt.payload.iat > 1725244200.into_int());
+        assert!(sd_cwt.payload.redacted_keys.is_none());
+        assert!(sd_cwt.unprotected.sd_claims.is_none());
+    }
+
+    #[test]
+    fn should_inline_custom_claims() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "3: https://verifier.example\nemail: johndoe@example.com\n-70000: 1"
+            .parse::<InputClaims>()
+            .unwrap();
+
+        let sd_cwt = issuer.sign(input).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        let payload = crate::cbor::from_slice(&sd_cwt.payload.to_cbor_bytes()).unwrap();
+        let keys = payload
+            .as_map()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        assert!(keys.contains(&CborValue::from("email")));
+        assert!(keys.contains(&CborValue::from(-70000)));
+        assert!(!keys.contains(&CborValue::from("custom")));
+    }
+
+    #[test]
+    fn should_parse_foreign_private_claims() {
+        let payload = CborValue::Map(vec![
+            ("private".into(), CborValue::Array(vec![1.into()])),
+            (3.into(), "https://verifier.example".into()),
+            (6.into(), 1725244200.into()),
+            (500.into(), CborValue::Bool(true)),
+        ]);
+        let bytes = crate::cbor::to_vec(&payload).unwrap();
+
+        let sd_payload = SdPayload::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(sd_payload.custom.len(), 2);
+        assert_eq!(
+            sd_payload.custom.get(&Keyy::new_int(500.into_int())),
+            Some(&Anyy::Bool(true))
+        );
+        // the original claims order is preserved
+        assert_eq!(sd_payload.to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn should_fail_parsing_duplicate_private_claims() {
+        for (key, reported) in [
+            (CborValue::from(-500), "-500"),
+            ("email".into(), "\"email\""),
+        ] {
+            let payload = CborValue::Map(vec![
+                (3.into(), "https://verifier.example".into()),
+                (6.into(), 1725244200.into()),
+                (key.clone(), 1.into()),
+                (key, 2.into()),
+            ]);
+            let bytes = crate::cbor::to_vec(&payload).unwrap();
+            let err = SdPayload::from_cbor_bytes(&bytes).unwrap_err();
+            assert!(err
+                .to_string()
+                .ends_with(&format!("Duplicate key: {reported}")));
+        }
+    }
+
+    #[test]
+    fn should_hash_with_sd_alg() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "3: https://verifier.example\n1114: -43\n!sd given_name: John"
+            .parse::<InputClaims>()
+            .unwrap();
+
+        let sd_cwt = issuer.sign(input).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        let payload = &sd_cwt.payload;
+        assert_eq!(payload.sd_alg, Some((-43).into_int()));
+        assert!(payload.custom.is_empty());
+        let disclosure = &sd_cwt.unprotected.sd_claims.as_ref().unwrap()[0];
+        let digest = HashAlgorithm::Sha384.digest(disclosure.to_cbor_bytes());
+        assert_eq!(payload.redacted_keys, Some(vec![digest]));
+    }
+
+    #[test]
+    fn should_fail_with_unsupported_sd_alg() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "3: https://verifier.example\n1114: -18"
+            .parse::<InputClaims>()
+            .unwrap();
+        assert!(matches!(
+            issuer.sign(input),
+            Err(SdCwtError::UnsupportedHashAlgorithm(alg)) if alg == "-18"
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_reserved_labels() {
+        let issuer = IssuerPrivateKey::generate();
+        for label in [SD_CLAIMS_LABEL, SD_KBT_LABEL, 1113, REDACTED_KEYS_LABEL] {
+            let input = format!("3: https://verifier.example\n{label}: 1")
+                .parse::<InputClaims>()
+                .unwrap();
+            assert!(matches!(
+                issuer.sign(input),
+                Err(SdCwtError::ReservedClaim(l)) if l == label
+            ));
+        }
+    }
+
+    #[test]
+    fn should_fail_without_audience() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "!sd given_name: John".parse::<InputClaims>().unwrap();
+
+        assert!(matches!(
+            issuer.sign(input),
+            Err(SdCwtError::MissingClaim(3))
+        ));
+    }
+
+    #[test]
+    fn should_redact_array_elements() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            nationalities:
+                - !sd US
+                - DE
+        "#;
+        let input = yaml.parse::<InputClaims>().unwrap();
+
+        let sd_cwt = issuer.sign(input).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        let Some(Anyy::Array { elements, .. }) = sd_cwt
+            .payload
+            .custom
+            .get(&Keyy::new_text("nationalities".to_string()))
+        else {
+            panic!("nationalities should be an array")
+        };
+        assert_eq!(elements[1], Anyy::new_text("DE".to_string()));
+
+        let disclosure = &sd_cwt.unprotected.sd_claims.as_ref().unwrap()[0];
+        let Salted::SaltedElement { salted_element, .. } = disclosure else {
+            panic!("should disclose an array element")
+        };
+        assert_eq!(salted_element.value, Anyy::new_text("US".to_string()));
+        let digest = HashAlgorithm::Sha256.digest(disclosure.to_cbor_bytes());
+        assert_eq!(
+            redacted_element_digest(&elements[0]),
+            Some(digest.as_slice())
+        );
+        assert!(sd_cwt.payload.redacted_keys.is_none());
+    }
+
+    #[test]
+    fn should_add_decoy_elements() {
+        let issuer = IssuerPrivateKey::generate().with_decoys(Decoys::Fixed(2));
+        let yaml = r#"
+            3: https://verifier.example
+            nationalities:
+                - !sd US
+                - DE
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        let Some(Anyy::Array { elements, .. }) = sd_cwt
+            .payload
+            .custom
+            .get(&Keyy::new_text("nationalities".to_string()))
+        else {
+            panic!("nationalities should be an array")
+        };
+        assert_eq!(elements.len(), 4);
+        let redacted = elements
+            .iter()
+            .filter(|e| redacted_element_digest(e).is_some())
+            .count();
+        assert_eq!(redacted, 3);
+    }
+}
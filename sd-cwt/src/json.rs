@@ -0,0 +1,228 @@
+use serde_json::Value as JsonValue;
+use serde_yaml::{value::TaggedValue, Mapping, Number, Value as YamlValue};
+
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::input::{sd_tag, untagged, InputClaims};
+use crate::path::{ClaimPath, PathSegment};
+
+/// Key of the single entry of a JSON object wrapping a disclosable value, e.g.
+/// `{ "given_name": { "!sd": "John" } }`
+pub const JSON_SD_MARKER: &str = "!sd";
+
+impl InputClaims {
+    /// Claims set written in JSON whose disclosable claims and array elements are designated by
+    /// their path e.g. `/address/street_address` or `/nationalities/0`
+    pub fn from_json(json: &JsonValue, paths: &[ClaimPath]) -> SdCwtResult<Self> {
+        let mut raw = json_to_yaml(json, false);
+        for path in paths {
+            tag_pointer(&mut raw, path)?;
+        }
+        Self::from_tagged_yaml(raw)
+    }
+
+    /// Claims set written in JSON whose disclosable claims and array elements are wrapped in an
+    /// object with a single [JSON_SD_MARKER] entry
+    pub fn from_json_with_markers(json: &JsonValue) -> SdCwtResult<Self> {
+        Self::from_tagged_yaml(json_to_yaml(json, true))
+    }
+}
+
+/// Converts JSON into YAML, turning the marked values into `!sd` tagged keys or elements, just
+/// like they would have been written in YAML
+fn json_to_yaml(json: &JsonValue, markers: bool) -> YamlValue {
+    match json {
+        JsonValue::Null => YamlValue::Null,
+        JsonValue::Bool(b) => YamlValue::Bool(*b),
+        JsonValue::Number(n) => {
+            let n = match (n.as_u64(), n.as_i64()) {
+                (Some(u), _) => Number::from(u),
+                (_, Some(i)) => Number::from(i),
+                _ => Number::from(n.as_f64().unwrap_or(f64::NAN)),
+            };
+            YamlValue::Number(n)
+        }
+        JsonValue::String(s) => YamlValue::String(s.clone()),
+        JsonValue::Array(elements) => YamlValue::Sequence(
+            elements
+                .iter()
+                .map(|element| json_to_yaml(element, markers))
+                .collect(),
+        ),
+        JsonValue::Object(entries) => match marked_value(json).filter(|_| markers) {
+            Some(value) => tagged(json_to_yaml(value, markers)),
+            None => YamlValue::Mapping(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = YamlValue::String(key.clone());
+                        match json_to_yaml(value, markers) {
+                            // the tag goes on the key of a claim
+                            YamlValue::Tagged(t) if markers && t.tag == sd_tag() => {
+                                (tagged(key), t.value)
+                            }
+                            value => (key, value),
+                        }
+                    })
+                    .collect(),
+            ),
+        },
+    }
+}
+
+fn marked_value(json: &JsonValue) -> Option<&JsonValue> {
+    let entries = json.as_object().filter(|entries| entries.len() == 1)?;
+    entries.get(JSON_SD_MARKER)
+}
+
+fn tagged(value: YamlValue) -> YamlValue {
+    YamlValue::Tagged(Box::new(TaggedValue {
+        tag: sd_tag(),
+        value,
+    }))
+}
+
+/// Tags the key or the array element designated by the path
+fn tag_pointer(raw: &mut YamlValue, path: &ClaimPath) -> SdCwtResult<()> {
+    let invalid_path = || SdCwtError::InvalidDisclosablePath(path.clone());
+    let (name, parents) = path.segments().split_last().ok_or_else(invalid_path)?;
+
+    let mut parent = raw;
+    for segment in parents {
+        parent = match parent {
+            YamlValue::Mapping(entries) => entries
+                .iter_mut()
+                .find_map(|(k, v)| is_key(segment, untagged(k)).then_some(v)),
+            YamlValue::Sequence(elements) => segment.index().and_then(|i| elements.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(invalid_path)?;
+    }
+
+    match parent {
+        YamlValue::Mapping(entries) => {
+            if !entries.keys().any(|k| is_key(name, untagged(k))) {
+                return Err(invalid_path());
+            }
+            // rebuilt to keep the claims order
+            *entries = std::mem::take(entries)
+                .into_iter()
+                .map(|(k, v)| match k {
+                    YamlValue::Tagged(_) => (k, v),
+                    k if is_key(name, &k) => (tagged(k), v),
+                    k => (k, v),
+                })
+                .collect::<Mapping>();
+        }
+        YamlValue::Sequence(elements) => {
+            let element = name
+                .index()
+                .and_then(|i| elements.get_mut(i))
+                .ok_or_else(invalid_path)?;
+            if !matches!(element, YamlValue::Tagged(_)) {
+                *element = tagged(std::mem::take(element));
+            }
+        }
+        _ => return Err(invalid_path()),
+    }
+    Ok(())
+}
+
+fn is_key(segment: &PathSegment, key: &YamlValue) -> bool {
+    match (segment, key) {
+        (PathSegment::Text(text), YamlValue::String(key)) => text == key,
+        (PathSegment::Int(i), YamlValue::String(key)) => i.to_string() == *key,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+        sub: user_42
+        !sd given_name: John
+        !sd address:
+            !sd street_address: "123 Main St"
+            country: US
+        nationalities:
+            - !sd US
+            - DE
+    "#;
+
+    fn claims() -> JsonValue {
+        serde_json::json!({
+            "sub": "user_42",
+            "given_name": "John",
+            "address": {
+                "street_address": "123 Main St",
+                "country": "US"
+            },
+            "nationalities": ["US", "DE"]
+        })
+    }
+
+    #[test]
+    fn should_parse_json_with_pointers() {
+        let paths = [
+            "/nationalities/0",
+            "/address/street_address",
+            "/given_name",
+            "/address",
+        ]
+        .map(|p| p.parse().unwrap());
+        let input = InputClaims::from_json(&claims(), &paths).unwrap();
+        let expected = YAML.parse::<InputClaims>().unwrap();
+
+        assert_eq!(input.raw, expected.raw);
+        assert_eq!(input.disclosable_paths, expected.disclosable_paths);
+        assert_eq!(input.to_json().unwrap(), claims());
+    }
+
+    #[test]
+    fn should_parse_json_with_markers() {
+        let json = serde_json::json!({
+            "sub": "user_42",
+            "given_name": { "!sd": "John" },
+            "address": {
+                "!sd": {
+                    "street_address": { "!sd": "123 Main St" },
+                    "country": "US"
+                }
+            },
+            "nationalities": [{ "!sd": "US" }, "DE"]
+        });
+        let input = InputClaims::from_json_with_markers(&json).unwrap();
+        let expected = YAML.parse::<InputClaims>().unwrap();
+
+        assert_eq!(input.raw, expected.raw);
+        assert_eq!(input.disclosable_paths, expected.disclosable_paths);
+        assert_eq!(input.to_json().unwrap(), claims());
+    }
+
+    #[test]
+    fn should_unescape_pointers() {
+        let json = serde_json::json!({ "a/b": 1, "c~d": 2, "3": 4 });
+        let paths = ["/a~1b", "/c~0d", "/3"].map(|p| p.parse().unwrap());
+        let input = InputClaims::from_json(&json, &paths).unwrap();
+        // JSON keys being text, `/3` designates the text key `"3"`
+        assert_eq!(input.disclosable_paths, vec!["/a~1b", "/c~0d", "/~23"]);
+        assert_eq!(
+            input.disclosable_paths[0].segments(),
+            [PathSegment::from("a/b")]
+        );
+    }
+
+    #[test]
+    fn should_fail_with_unknown_pointer() {
+        let paths = ["/address/locality".parse().unwrap()];
+        assert!(matches!(
+            InputClaims::from_json(&claims(), &paths),
+            Err(SdCwtError::InvalidDisclosablePath(p)) if p == "/address/locality"
+        ));
+        assert!(matches!(
+            InputClaims::from_json(&claims(), &[ClaimPath::root()]),
+            Err(SdCwtError::InvalidDisclosablePath(p)) if p.is_root()
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+use ciborium::Value as CborValue;
+use sd_cwt_types::{
+    ordered_hash_map::OrderedHashMap, serialization::Deserialize, SdPayload, Unprotected,
+};
+
+use crate::cbor;
+use crate::cose_key::Confirmation;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::hash::HashAlgorithm;
+use crate::issuer::{now, sign_cose_sign1, SD_KBT_LABEL};
+use crate::signing::{SigningAlgorithm, SigningKey};
+use crate::types;
+use crate::verifier::VerifierPublicKey;
+use crate::SdCwt;
+
+/// Content type of a key binding token
+pub const KBT_TYP: &str = "application/kb+cwt";
+
+/// Key of the holder, confirmed by the issuer in the `cnf` claim, used to bind presentations
+pub struct HolderPrivateKey(SigningKey);
+
+impl HolderPrivateKey {
+    pub fn new(key: impl Into<SigningKey>) -> Self {
+        Self(key.into())
+    }
+
+    /// Generates an EdDSA key
+    pub fn generate() -> Self {
+        Self::generate_with_algorithm(SigningAlgorithm::EdDsa)
+    }
+
+    pub fn generate_with_algorithm(alg: SigningAlgorithm) -> Self {
+        Self(SigningKey::generate(alg))
+    }
+
+    pub fn public_key(&self) -> VerifierPublicKey {
+        self.0.public_key()
+    }
+}
+
+impl SdCwt {
+    /// Binds this presentation to its holder by attaching a key binding token covering the
+    /// audience, the verifier's nonce, the current time and the digest of the presentation.
+    /// This has to be done once the disclosures have been selected.
+    pub fn bind(
+        &self,
+        holder_key: &HolderPrivateKey,
+        audience: &str,
+        cnonce: Option<Vec<u8>>,
+    ) -> SdCwtResult<SdCwt> {
+        let mut payload = SdPayload::builder()
+            .audience(audience)
+            .issued_at(now()?)
+            .sd_hash(presentation_digest(self.as_bytes())?)
+            .build()?;
+        payload.cnonce = cnonce;
+        let unprotected = Unprotected::new(OrderedHashMap::new());
+        let kbt = sign_cose_sign1(&holder_key.0, KBT_TYP, unprotected, payload)?;
+
+        let kbt = CborValue::Bytes(kbt);
+        let bytes = cbor::replace_unprotected(self.as_bytes(), SD_KBT_LABEL, Some(&kbt))?;
+        Ok(SdCwt(bytes))
+    }
+}
+
+/// Digest of the bytes of a presentation, as received, without any key binding token it might
+/// already carry, computed with the hash algorithm of its disclosures
+pub(crate) fn presentation_digest(sd_cwt: &[u8]) -> SdCwtResult<Vec<u8>> {
+    let bytes = cbor::replace_unprotected(sd_cwt, SD_KBT_LABEL, None)?;
+    let payload = types::SdCwt::from_cbor_bytes(&bytes)?.payload;
+    let hash_alg = HashAlgorithm::from_sd_alg(payload.sd_alg.as_ref())?;
+    Ok(hash_alg.digest(bytes))
+}
+
+/// Extracts the key binding token from the unprotected header of a presentation
+pub(crate) fn key_binding_token(sd_cwt: &CborValue) -> SdCwtResult<&[u8]> {
+    let sd_kbt_label = CborValue::from(SD_KBT_LABEL);
+    cbor::unprotected(sd_cwt)?
+        .iter()
+        .find(|(k, _)| *k == sd_kbt_label)
+        .and_then(|(_, v)| v.as_bytes())
+        .map(Vec::as_slice)
+        .ok_or(SdCwtError::MissingKeyBinding)
+}
+
+/// Extracts the holder key confirmed by the issuer in the `cnf` claim
+pub(crate) fn holder_key(payload: &SdPayload) -> SdCwtResult<VerifierPublicKey> {
+    Confirmation::from_payload(payload)?
+        .as_ref()
+        .and_then(Confirmation::cose_key)
+        .ok_or(SdCwtError::MissingHolderKey)?
+        .try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputClaims;
+    use crate::validation::VerificationOptions;
+    use crate::IssuerPrivateKey;
+    use std::time::Duration;
+
+    const YAML: &str = r#"
+        3: https://verifier.example
+        !sd given_name: John
+        !sd family_name: Doe
+    "#;
+    const AUDIENCE: &str = "https://verifier.example";
+
+    fn issue(holder: &HolderPrivateKey) -> (IssuerPrivateKey, SdCwt) {
+        let issuer = IssuerPrivateKey::generate();
+        let input = YAML.parse::<InputClaims>().unwrap();
+        let sd_cwt = issuer
+            .sign_with_holder_key(input, &holder.public_key())
+            .unwrap();
+        (issuer, sd_cwt)
+    }
+
+    #[test]
+    fn should_bind_presentation() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt
+            .present(&["/given_name"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        let presentation = presentation
+            .bind(&holder, AUDIENCE, Some(b"nonce".to_vec()))
+            .unwrap();
+
+        holder
+            .public_key()
+            .verify_key_binding(&presentation, AUDIENCE, Some(b"nonce"))
+            .unwrap();
+
+        let kbt = sd_cwt_types::SdCwt::from_cbor_bytes(presentation.as_bytes())
+            .unwrap()
+            .unprotected
+            .sd_kbt
+            .unwrap();
+        let kbt = sd_cwt_types::SdCwtKbt::from_cbor_bytes(&kbt).unwrap();
+        assert_eq!(kbt.protected.typ, KBT_TYP);
+        assert_eq!(kbt.payload.aud, AUDIENCE);
+        assert_eq!(kbt.payload.cnonce.as_deref(), Some(&b"nonce"[..]));
+    }
+
+    #[test]
+    fn should_rebind_presentation() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, "https://other.example", None).unwrap();
+        let presentation = presentation.bind(&holder, AUDIENCE, None).unwrap();
+
+        holder
+            .public_key()
+            .verify_key_binding(&presentation, AUDIENCE, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn should_verify_presentation() {
+        let holder = HolderPrivateKey::generate();
+        let (issuer, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt
+            .present(&["/family_name"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        let presentation = presentation
+            .bind(&holder, AUDIENCE, Some(b"nonce".to_vec()))
+            .unwrap();
+
+        let payload = issuer
+            .public_key()
+            .verify_presentation(&presentation, AUDIENCE, Some(b"nonce"))
+            .unwrap();
+        assert!(payload
+            .custom
+            .contains_key(&sd_cwt_types::Keyy::new_text("family_name".to_string())));
+    }
+
+    #[test]
+    fn should_fail_when_bound_by_another_holder() {
+        let holder = HolderPrivateKey::generate();
+        let (issuer, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt
+            .bind(&HolderPrivateKey::generate(), AUDIENCE, None)
+            .unwrap();
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify_presentation(&presentation, AUDIENCE, None),
+            Err(SdCwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn should_refuse_unbound_presentation() {
+        let holder = HolderPrivateKey::generate();
+        let (issuer, sd_cwt) = issue(&holder);
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify_presentation(&sd_cwt, AUDIENCE, None),
+            Err(SdCwtError::MissingKeyBinding)
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_other_holder_key() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt
+            .bind(&HolderPrivateKey::generate(), AUDIENCE, None)
+            .unwrap();
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding(&presentation, AUDIENCE, None),
+            Err(SdCwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_wrong_audience() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, "https://other.example", None).unwrap();
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding(&presentation, AUDIENCE, None),
+            Err(SdCwtError::KeyBindingAudienceMismatch(aud)) if aud == "https://other.example"
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_stale_key_binding_token() {
+        let holder = HolderPrivateKey::generate();
+        let (issuer, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, AUDIENCE, None).unwrap();
+        let iat = now().unwrap();
+
+        let later = VerificationOptions::default().with_clock(move || iat + 600);
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify_presentation_with(&presentation, AUDIENCE, None, &later),
+            Err(SdCwtError::TooOld { max_age: 300, .. })
+        ));
+        let later = later.with_key_binding_max_age(Duration::from_secs(900));
+        assert!(issuer
+            .public_key()
+            .verify_presentation_with(&presentation, AUDIENCE, None, &later)
+            .is_ok());
+
+        let earlier = VerificationOptions::default().with_clock(move || iat - 60);
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding_with(&presentation, AUDIENCE, None, &earlier),
+            Err(SdCwtError::IssuedInFuture { .. })
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_wrong_nonce() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt
+            .bind(&holder, AUDIENCE, Some(b"replayed".to_vec()))
+            .unwrap();
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding(&presentation, AUDIENCE, Some(b"nonce")),
+            Err(SdCwtError::KeyBindingNonceMismatch)
+        ));
+    }
+
+    #[test]
+    fn should_fail_when_disclosures_changed_after_binding() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, AUDIENCE, None).unwrap();
+        let presentation = presentation
+            .present(&["/given_name"].map(|p| p.parse().unwrap()))
+            .unwrap();
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding(&presentation, AUDIENCE, None),
+            Err(SdCwtError::KeyBindingHashMismatch)
+        ));
+    }
+
+    /// Replaces the encoding of the `sd_claims` label, first in the unprotected header, by a
+    /// longer yet valid one
+    fn lengthen_sd_claims_label(sd_cwt: &SdCwt) -> SdCwt {
+        let mut bytes = sd_cwt.as_bytes().to_vec();
+        let protected = cbor::from_slice(&bytes).unwrap().into_tag().unwrap().1;
+        let protected = protected.into_array().unwrap().remove(0);
+        // tag 18 and array of 4 items heads, protected header then unprotected map head
+        let label = 2 + cbor::to_vec(&protected).unwrap().len() + 1;
+        assert_eq!(bytes[label..label + 3], [0x19, 0x04, 0x57]);
+        bytes.splice(label..label + 3, [0x1a, 0x00, 0x00, 0x04, 0x57]);
+        SdCwt(bytes)
+    }
+
+    #[test]
+    fn should_digest_received_bytes() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let sd_cwt = lengthen_sd_claims_label(&sd_cwt);
+        let presentation = sd_cwt.bind(&holder, AUDIENCE, None).unwrap();
+        holder
+            .public_key()
+            .verify_key_binding(&presentation, AUDIENCE, None)
+            .unwrap();
+
+        // the header entries are kept as they were received
+        let bytes = presentation.as_bytes();
+        assert!(bytes
+            .windows(5)
+            .any(|w| w == [0x1a, 0x00, 0x00, 0x04, 0x57]));
+        assert_eq!(
+            presentation_digest(bytes).unwrap(),
+            HashAlgorithm::Sha256.digest(sd_cwt.as_bytes())
+        );
+    }
+
+    #[test]
+    fn should_fail_when_re_encoded_after_binding() {
+        let holder = HolderPrivateKey::generate();
+        let (_, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, AUDIENCE, None).unwrap();
+        let presentation = lengthen_sd_claims_label(&presentation);
+        assert!(matches!(
+            holder
+                .public_key()
+                .verify_key_binding(&presentation, AUDIENCE, None),
+            Err(SdCwtError::KeyBindingHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn should_bind_with_ecdsa_holder_key() {
+        let holder = HolderPrivateKey::generate_with_algorithm(SigningAlgorithm::Es384);
+        let (issuer, sd_cwt) = issue(&holder);
+        let presentation = sd_cwt.bind(&holder, AUDIENCE, None).unwrap();
+        issuer
+            .public_key()
+            .verify_presentation(&presentation, AUDIENCE, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn should_convert_cose_key() {
+        for alg in [
+            SigningAlgorithm::EdDsa,
+            SigningAlgorithm::Es256,
+            SigningAlgorithm::Es384,
+            SigningAlgorithm::Es512,
+        ] {
+            let holder = HolderPrivateKey::generate_with_algorithm(alg);
+            let cose_key = holder.public_key().to_cose_key();
+            let pk = VerifierPublicKey::from_cose_key(&cose_key).unwrap();
+            assert_eq!(pk.algorithm(), alg);
+            assert_eq!(pk.to_bytes(), holder.public_key().to_bytes());
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use ciborium::Value as CborValue;
+use sd_cwt_types::{serialization::ToCBORBytes, SdPayload};
+
+use crate::cbor;
+use crate::error::SdCwtResult;
+
+/// Claims registered for CWT along with their JWT name
+pub(crate) const REGISTERED_CLAIMS: [(&str, i64); 8] = [
+    ("iss", 1),
+    ("sub", 2),
+    ("aud", 3),
+    ("exp", 4),
+    ("nbf", 5),
+    ("iat", 6),
+    ("cnf", 8),
+    ("cnonce", 39),
+];
+
+/// Translates top-level claim names, as used in JWTs, into CWT labels and back. Starts with the
+/// registered claims and can be extended with private-use labels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimLabels(Vec<(String, i64)>);
+
+impl Default for ClaimLabels {
+    fn default() -> Self {
+        Self(
+            REGISTERED_CLAIMS
+                .iter()
+                .map(|(name, label)| (name.to_string(), *label))
+                .collect(),
+        )
+    }
+}
+
+impl ClaimLabels {
+    /// Maps `name` to `label`, replacing any previous mapping of either of them
+    pub fn with_label(mut self, name: impl Into<String>, label: i64) -> Self {
+        let name = name.into();
+        self.0.retain(|(n, l)| *n != name && *l != label);
+        self.0.push((name, label));
+        self
+    }
+
+    pub fn label(&self, name: &str) -> Option<i64> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, l)| *l)
+    }
+
+    pub fn name(&self, label: i64) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, l)| *l == label)
+            .map(|(n, _)| n.as_str())
+    }
+
+    /// Claims set of a verified payload where the known labels have been replaced by their name
+    pub fn named_claims(&self, payload: &SdPayload) -> SdCwtResult<CborValue> {
+        let claims_set = cbor::from_slice(&payload.to_cbor_bytes())?;
+        let CborValue::Map(entries) = claims_set else {
+            return Ok(claims_set);
+        };
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let name = key
+                    .as_integer()
+                    .and_then(|i| i64::try_from(i).ok())
+                    .and_then(|label| self.name(label));
+                match name {
+                    Some(name) => (CborValue::from(name), value),
+                    None => (key, value),
+                }
+            })
+            .collect();
+        Ok(CborValue::Map(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_registered_claims() {
+        let labels = ClaimLabels::default();
+        assert_eq!(labels.label("iss"), Some(1));
+        assert_eq!(labels.label("cnonce"), Some(39));
+        assert_eq!(labels.name(8), Some("cnf"));
+        assert_eq!(labels.label("given_name"), None);
+    }
+
+    #[test]
+    fn should_register_private_label() {
+        let labels = ClaimLabels::default()
+            .with_label("tenant", -65538)
+            .with_label("subject", 2);
+        assert_eq!(labels.label("tenant"), Some(-65538));
+        assert_eq!(labels.name(2), Some("subject"));
+        assert_eq!(labels.label("sub"), None);
+    }
+}
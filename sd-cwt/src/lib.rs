@@ -1,29 +1,50 @@
-use jwt_simple::prelude::*;
-
-use crate::error::SdCwtResult;
-use crate::types::SdCwtPayload;
+pub use cose_key::{Confirmation, CoseKey, CoseKeyParams};
+pub use decoy::Decoys;
+pub use hash::HashAlgorithm;
+pub use holder::Disclosure;
+pub use issuer::IssuerPrivateKey;
+pub use kbt::HolderPrivateKey;
+pub use labels::ClaimLabels;
+pub use path::{ClaimPath, PathSegment};
+pub use signing::{SigningAlgorithm, SigningKey};
+pub use validation::{Accepted, RegisteredClaims, VerificationOptions};
+pub use verifier::VerifierPublicKey;
 
 pub mod error;
 pub mod types;
 pub mod input;
-
-pub struct IssuerPrivateKey(Ed25519KeyPair);
+pub mod cose_key;
+pub mod decoy;
+pub mod edn;
+pub mod hash;
+pub mod holder;
+pub mod issuer;
+pub mod kbt;
+pub mod json;
+pub mod labels;
+pub mod path;
+pub mod signing;
+pub mod validation;
+pub mod verifier;
+mod cbor;
 
 pub struct SdCwt(Vec<u8>);
 
-impl std::fmt::Display for SdCwt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:x?}", self.0)
+impl SdCwt {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
     }
 }
 
-impl IssuerPrivateKey {
-    pub fn generate() -> Self {
-        Self(Ed25519KeyPair::generate())
+impl From<Vec<u8>> for SdCwt {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
     }
+}
 
-    pub fn sign(&self, claims: SdCwtPayload) -> SdCwtResult<SdCwt> {
-        todo!()
+impl std::fmt::Display for SdCwt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:x?}", self.0)
     }
 }
 
@@ -34,7 +55,9 @@ mod tests {
     #[test]
     fn should_succeed() {
         let issuer = IssuerPrivateKey::generate();
-        // let sd_cwt = issuer.sign().unwrap();
-        // println!("{sd_cwt}");
+        let input = "3: https://verifier.example".parse::<input::InputClaims>().unwrap();
+        let sd_cwt = issuer.sign(input).unwrap();
+        let payload = issuer.public_key().verify(&sd_cwt).unwrap();
+        assert_eq!(payload.aud, "https://verifier.example");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ciborium::Value as CborValue;
+use itertools::Itertools;
+use sd_cwt_types::{Anyy, Keyy, SdPayload};
+
+use crate::cbor::int_to_i64;
+use crate::error::{SdCwtError, SdCwtResult};
+
+/// Map key or array index within a [ClaimPath]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    /// Integer map key (e.g. a CWT label) or array index
+    Int(i64),
+    /// Text map key
+    Text(String),
+}
+
+impl PathSegment {
+    /// Whether this segment designates the map key. An integer segment only designates an integer
+    /// key and a text segment a text key, even one spelling an integer.
+    pub fn is_key(&self, key: &CborValue) -> bool {
+        match (self, key) {
+            (Self::Int(i), CborValue::Integer(key)) => i128::from(*i) == i128::from(*key),
+            (Self::Text(text), CborValue::Text(key)) => text == key,
+            _ => false,
+        }
+    }
+
+    /// Array index designated by this segment
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Self::Int(i) => usize::try_from(*i).ok(),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// Segment of a text or integer map key
+    pub(crate) fn from_key(key: &CborValue) -> Option<Self> {
+        match key {
+            CborValue::Text(text) => Some(Self::Text(text.clone())),
+            CborValue::Integer(i) => i64::try_from(*i).ok().map(Self::Int),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_keyy(key: &Keyy) -> Option<Self> {
+        match key {
+            Keyy::Int(i) => int_to_i64(i).map(Self::Int),
+            Keyy::Text { text, .. } => Some(Self::Text(text.clone())),
+        }
+    }
+}
+
+impl From<i64> for PathSegment {
+    fn from(i: i64) -> Self {
+        Self::Int(i)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        Self::Int(index as i64)
+    }
+}
+
+impl From<&str> for PathSegment {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Text(text) if canonical_int(text).is_some() => write!(f, "{NUMERIC_TEXT}{text}"),
+            Self::Text(text) => write!(f, "{}", text.replace('~', "~0").replace('/', "~1")),
+        }
+    }
+}
+
+/// Prefix of a text segment spelling a canonical integer, telling it from an integer segment. Not
+/// an RFC 6901 escape, standard JSON pointer implementations reject it.
+const NUMERIC_TEXT: &str = "~2";
+
+/// Integer spelled by `text`, unless it has a sign, leading zeros or does not fit
+fn canonical_int(text: &str) -> Option<i64> {
+    text.parse::<i64>().ok().filter(|i| i.to_string() == text)
+}
+
+/// Path of a claim or of an array element within a claims set, written like a JSON pointer
+/// (RFC 6901) e.g. `/address/street_address` or `/nationalities/0`. The empty path designates the
+/// claims set itself.
+///
+/// CBOR maps having both integer and text keys, the syntax is a private superset of RFC 6901: a
+/// text map key spelling an integer is escaped with `~2` e.g. `/~23` for the text key `"3"`, `/3`
+/// being the integer key or array index. Such paths are not valid JSON pointers and are rejected
+/// by standard tools; paths without text keys spelling an integer are plain JSON pointers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClaimPath(Vec<PathSegment>);
+
+impl ClaimPath {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.is_root()
+    }
+
+    /// Path of the map or array holding this claim, `None` for the claims set itself
+    pub fn parent(&self) -> Option<Self> {
+        self.0
+            .split_last()
+            .map(|(_, parents)| Self(parents.to_vec()))
+    }
+
+    pub fn last(&self) -> Option<&PathSegment> {
+        self.0.last()
+    }
+
+    /// Path of the claim or element `segment` within this one
+    pub fn join(&self, segment: impl Into<PathSegment>) -> Self {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    pub fn push(&mut self, segment: impl Into<PathSegment>) {
+        self.0.push(segment.into());
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.0.pop()
+    }
+
+    /// Whether this path goes through `ancestor`, or is the same
+    pub fn starts_with(&self, ancestor: &Self) -> bool {
+        self.0.starts_with(&ancestor.0)
+    }
+
+    /// Custom claim, or nested value, of a verified payload designated by this path
+    pub fn lookup<'a>(&self, payload: &'a SdPayload) -> Option<&'a Anyy> {
+        let (first, rest) = self.0.split_first()?;
+        let claim = payload
+            .custom
+            .iter()
+            .find_map(|(k, v)| (PathSegment::from_keyy(k).as_ref() == Some(first)).then_some(v))?;
+        rest.iter().try_fold(claim, |parent, segment| match parent {
+            Anyy::Map { entries, .. } => entries.iter().find_map(|(k, v)| {
+                let key = CborValue::try_from(k.clone()).ok()?;
+                segment.is_key(&key).then_some(v)
+            }),
+            Anyy::Array { elements, .. } => segment.index().and_then(|i| elements.get(i)),
+            _ => None,
+        })
+    }
+}
+
+impl From<Vec<PathSegment>> for ClaimPath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self(segments)
+    }
+}
+
+impl FromStr for ClaimPath {
+    type Err = SdCwtError;
+
+    /// Parses a JSON pointer, where a segment spelling a canonical integer is an integer unless
+    /// escaped with the non-standard `~2`
+    fn from_str(s: &str) -> SdCwtResult<Self> {
+        if s.is_empty() {
+            return Ok(Self::root());
+        }
+        let invalid = || SdCwtError::InvalidClaimPath(s.to_string());
+        s.strip_prefix('/')
+            .ok_or_else(invalid)?
+            .split('/')
+            .map(|segment| {
+                if let Some(i) = canonical_int(segment) {
+                    return Ok(PathSegment::Int(i));
+                }
+                if let Some(text) = segment.strip_prefix(NUMERIC_TEXT) {
+                    return canonical_int(text)
+                        .map(|_| PathSegment::from(text))
+                        .ok_or_else(invalid);
+                }
+                let mut text = String::with_capacity(segment.len());
+                let mut chars = segment.chars();
+                while let Some(c) = chars.next() {
+                    if c != '~' {
+                        text.push(c);
+                        continue;
+                    }
+                    match chars.next() {
+                        Some('0') => text.push('~'),
+                        Some('1') => text.push('/'),
+                        _ => return Err(invalid()),
+                    }
+                }
+                Ok(PathSegment::Text(text))
+            })
+            .collect::<SdCwtResult<Vec<_>>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for ClaimPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().map(|s| format!("/{s}")).join(""))
+    }
+}
+
+/// Compares the JSON pointer spelling of the path
+impl PartialEq<&str> for ClaimPath {
+    fn eq(&self, other: &&str) -> bool {
+        format!("{self}") == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_and_display() {
+        let path = "/address/street_address".parse::<ClaimPath>().unwrap();
+        assert_eq!(path.segments(), ["address".into(), "street_address".into()]);
+        assert_eq!(path.to_string(), "/address/street_address");
+
+        let path = "/nationalities/0/-65537".parse::<ClaimPath>().unwrap();
+        assert_eq!(
+            path.segments(),
+            ["nationalities".into(), 0usize.into(), (-65537i64).into()]
+        );
+
+        // not canonical integers
+        let path = "/007/+1".parse::<ClaimPath>().unwrap();
+        assert_eq!(path.segments(), ["007".into(), "+1".into()]);
+
+        assert!("".parse::<ClaimPath>().unwrap().is_root());
+        assert_eq!(ClaimPath::root().to_string(), "");
+    }
+
+    #[test]
+    fn should_escape() {
+        let path = ClaimPath::root().join("a/b").join("c~d").join("~1");
+        assert_eq!(path.to_string(), "/a~1b/c~0d/~01");
+        assert_eq!(path.to_string().parse::<ClaimPath>().unwrap(), path);
+        assert_ne!(path, ClaimPath::root().join("a").join("b"));
+    }
+
+    #[test]
+    fn should_escape_numeric_text() {
+        let path = ClaimPath::root()
+            .join("3")
+            .join(3i64)
+            .join("-1")
+            .join("007");
+        assert_eq!(path.to_string(), "/~23/3/~2-1/007");
+        assert_eq!(path.to_string().parse::<ClaimPath>().unwrap(), path);
+        assert_eq!(
+            "/3".parse::<ClaimPath>().unwrap(),
+            ClaimPath::root().join(3i64)
+        );
+    }
+
+    #[test]
+    fn should_fail_parsing() {
+        for invalid in ["address", "/a~2", "/a~", "/~2", "/~2a", "/~2007"] {
+            assert!(matches!(
+                invalid.parse::<ClaimPath>(),
+                Err(SdCwtError::InvalidClaimPath(p)) if p == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn should_match_keys() {
+        assert!(PathSegment::Int(1).is_key(&CborValue::from(1)));
+        assert!(!PathSegment::Int(1).is_key(&CborValue::from("1")));
+        assert!(!PathSegment::Text("1".into()).is_key(&CborValue::from(1)));
+        assert!(PathSegment::Text("1".into()).is_key(&CborValue::from("1")));
+        assert!(PathSegment::Text("a".into()).is_key(&CborValue::from("a")));
+    }
+}
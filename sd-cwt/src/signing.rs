@@ -0,0 +1,176 @@
+use jwt_simple::prelude::*;
+use p256::ecdsa::signature::Signer;
+
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::verifier::VerifierPublicKey;
+
+/// COSE algorithm identifier for ECDSA with P-256 and SHA-256
+pub const COSE_ALG_ES256: i64 = -7;
+/// COSE algorithm identifier for EdDSA
+pub const COSE_ALG_EDDSA: i64 = -8;
+/// COSE algorithm identifier for ECDSA with P-384 and SHA-384
+pub const COSE_ALG_ES384: i64 = -35;
+/// COSE algorithm identifier for ECDSA with P-521 and SHA-512
+pub const COSE_ALG_ES512: i64 = -36;
+
+/// Algorithm signing the COSE_Sign1 of an SD-CWT or of a key binding token, advertised in the
+/// `alg` protected header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    #[default]
+    EdDsa,
+    Es256,
+    Es384,
+    Es512,
+}
+
+impl SigningAlgorithm {
+    pub fn cose_alg(&self) -> i64 {
+        match self {
+            Self::EdDsa => COSE_ALG_EDDSA,
+            Self::Es256 => COSE_ALG_ES256,
+            Self::Es384 => COSE_ALG_ES384,
+            Self::Es512 => COSE_ALG_ES512,
+        }
+    }
+}
+
+impl TryFrom<i64> for SigningAlgorithm {
+    type Error = SdCwtError;
+
+    fn try_from(alg: i64) -> SdCwtResult<Self> {
+        match alg {
+            COSE_ALG_EDDSA => Ok(Self::EdDsa),
+            COSE_ALG_ES256 => Ok(Self::Es256),
+            COSE_ALG_ES384 => Ok(Self::Es384),
+            COSE_ALG_ES512 => Ok(Self::Es512),
+            _ => Err(SdCwtError::UnsupportedSigningAlgorithm(alg.to_string())),
+        }
+    }
+}
+
+/// Private key of any supported [SigningAlgorithm]
+pub enum SigningKey {
+    EdDsa(Ed25519KeyPair),
+    Es256(ES256KeyPair),
+    Es384(ES384KeyPair),
+    Es512(p521::ecdsa::SigningKey),
+}
+
+impl SigningKey {
+    pub fn generate(alg: SigningAlgorithm) -> Self {
+        match alg {
+            SigningAlgorithm::EdDsa => Self::EdDsa(Ed25519KeyPair::generate()),
+            SigningAlgorithm::Es256 => Self::Es256(ES256KeyPair::generate()),
+            SigningAlgorithm::Es384 => Self::Es384(ES384KeyPair::generate()),
+            SigningAlgorithm::Es512 => {
+                Self::Es512(p521::ecdsa::SigningKey::random(&mut rand::thread_rng()))
+            }
+        }
+    }
+
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        match self {
+            Self::EdDsa(_) => SigningAlgorithm::EdDsa,
+            Self::Es256(_) => SigningAlgorithm::Es256,
+            Self::Es384(_) => SigningAlgorithm::Es384,
+            Self::Es512(_) => SigningAlgorithm::Es512,
+        }
+    }
+
+    pub fn public_key(&self) -> VerifierPublicKey {
+        match self {
+            Self::EdDsa(kp) => VerifierPublicKey::from(kp.public_key()),
+            Self::Es256(kp) => VerifierPublicKey::from(kp.public_key()),
+            Self::Es384(kp) => VerifierPublicKey::from(kp.public_key()),
+            Self::Es512(sk) => VerifierPublicKey::from(p521::ecdsa::VerifyingKey::from(sk)),
+        }
+    }
+
+    /// Signs `tbs` and returns the raw signature as expected in a COSE_Sign1 i.e. `r || s` for
+    /// ECDSA
+    pub fn sign(&self, tbs: &[u8]) -> Vec<u8> {
+        match self {
+            Self::EdDsa(kp) => kp.key_pair().as_ref().sk.sign(tbs, None).to_vec(),
+            Self::Es256(kp) => {
+                let signature: p256::ecdsa::Signature = kp.key_pair().as_ref().sign(tbs);
+                signature.to_bytes().to_vec()
+            }
+            Self::Es384(kp) => {
+                let signature: p384::ecdsa::Signature = kp.key_pair().as_ref().sign(tbs);
+                signature.to_bytes().to_vec()
+            }
+            Self::Es512(sk) => {
+                let signature: p521::ecdsa::Signature = sk.sign(tbs);
+                signature.to_bytes().to_vec()
+            }
+        }
+    }
+}
+
+impl From<Ed25519KeyPair> for SigningKey {
+    fn from(kp: Ed25519KeyPair) -> Self {
+        Self::EdDsa(kp)
+    }
+}
+
+impl From<ES256KeyPair> for SigningKey {
+    fn from(kp: ES256KeyPair) -> Self {
+        Self::Es256(kp)
+    }
+}
+
+impl From<ES384KeyPair> for SigningKey {
+    fn from(kp: ES384KeyPair) -> Self {
+        Self::Es384(kp)
+    }
+}
+
+impl From<p521::ecdsa::SigningKey> for SigningKey {
+    fn from(sk: p521::ecdsa::SigningKey) -> Self {
+        Self::Es512(sk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGORITHMS: [SigningAlgorithm; 4] = [
+        SigningAlgorithm::EdDsa,
+        SigningAlgorithm::Es256,
+        SigningAlgorithm::Es384,
+        SigningAlgorithm::Es512,
+    ];
+
+    #[test]
+    fn should_round_trip_cose_alg() {
+        for alg in ALGORITHMS {
+            assert_eq!(SigningAlgorithm::try_from(alg.cose_alg()).unwrap(), alg);
+        }
+    }
+
+    #[test]
+    fn should_sign_and_verify() {
+        for alg in ALGORITHMS {
+            let key = SigningKey::generate(alg);
+            assert_eq!(key.algorithm(), alg);
+            let signature = key.sign(b"abc");
+            let pk = key.public_key();
+            assert_eq!(pk.algorithm(), alg);
+            pk.verify_raw(b"abc", &signature).unwrap();
+            assert!(matches!(
+                pk.verify_raw(b"abd", &signature),
+                Err(SdCwtError::InvalidSignature)
+            ));
+        }
+    }
+
+    #[test]
+    fn should_fail_with_unsupported_alg() {
+        assert!(matches!(
+            SigningAlgorithm::try_from(-257),
+            Err(SdCwtError::UnsupportedSigningAlgorithm(alg)) if alg == "-257"
+        ));
+    }
+}
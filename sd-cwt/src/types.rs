@@ -1,6 +1,47 @@
-use ciborium::tag::Accepted;
+//! Wire format of SD-CWTs, as generated from the CDDL of the specification in `sd-cwt-types`
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct SdCwtPayload {
-    
+use sd_cwt_types::serialization::{Deserialize, ToCBORBytes};
+pub use sd_cwt_types::{
+    BuilderError, NumericDate, Salted, SaltedClaimItem, SaltedElementItem, SdCwt, SdPayload,
+    SdPayloadBuilder, SdProtected, SdProtectedBuilder, Unprotected,
+};
+
+use crate::error::SdCwtResult;
+
+impl crate::SdCwt {
+    /// Decodes the COSE_Sign1 into its typed headers and payload
+    pub fn decode(&self) -> SdCwtResult<SdCwt> {
+        Ok(SdCwt::from_cbor_bytes(self.as_bytes())?)
+    }
+}
+
+impl From<&SdCwt> for crate::SdCwt {
+    fn from(sd_cwt: &SdCwt) -> Self {
+        Self::from(sd_cwt.to_cbor_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IssuerPrivateKey;
+
+    #[test]
+    fn should_decode() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "3: https://verifier.example\n!sd given_name: John"
+            .parse()
+            .unwrap();
+        let sd_cwt = issuer.sign(input).unwrap();
+        let decoded: SdCwt = sd_cwt.decode().unwrap();
+        assert!(decoded.payload.redacted_keys.is_some());
+        // re-encoding preserves the signed bytes
+        assert_eq!(crate::SdCwt::from(&decoded).as_bytes(), sd_cwt.as_bytes());
+
+        let truncated = crate::SdCwt::from(sd_cwt.as_bytes()[..10].to_vec());
+        assert!(matches!(
+            truncated.decode(),
+            Err(crate::error::SdCwtError::DeserializeError(_))
+        ));
+    }
 }
@@ -0,0 +1,461 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sd_cwt_types::{Int, SdPayload};
+
+use crate::cbor::int_to_i64;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::issuer::now;
+
+/// Source of the current time, in seconds since the Unix epoch
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SdCwtResult<i64>;
+}
+
+/// Clock of the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SdCwtResult<i64> {
+        now()
+    }
+}
+
+/// Any function returning the current time, e.g. a fixed one in tests
+impl<F: Fn() -> i64 + Send + Sync> Clock for F {
+    fn now(&self) -> SdCwtResult<i64> {
+        Ok(self())
+    }
+}
+
+/// Value accepted by a verifier for the `aud` or the `iss` claim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Accepted {
+    Exact(String),
+    /// Pattern where `*` stands for a single DNS label e.g. `https://*.tenant.example`, that is any
+    /// non-empty sequence of characters but `.`, `/`, `:`, `@`, `?` and `#`
+    Pattern(String),
+}
+
+impl Accepted {
+    pub fn exact(value: impl Into<String>) -> Self {
+        Self::Exact(value.into())
+    }
+
+    pub fn pattern(pattern: impl Into<String>) -> Self {
+        Self::Pattern(pattern.into())
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Exact(exact) => exact == value,
+            Self::Pattern(pattern) => matches_pattern(pattern, value),
+        }
+    }
+}
+
+/// Characters a wildcard does not stand for, so that it stays within a single DNS label
+const LABEL_SEPARATORS: [char; 6] = ['.', '/', ':', '@', '?', '#'];
+
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let Some((prefix, pattern)) = pattern.split_once('*') else {
+        return pattern == value;
+    };
+    let Some(value) = value.strip_prefix(prefix) else {
+        return false;
+    };
+    let label_len = value.find(LABEL_SEPARATORS).unwrap_or(value.len());
+    (1..=label_len)
+        .filter(|i| value.is_char_boundary(*i))
+        .any(|i| matches_pattern(pattern, &value[i..]))
+}
+
+/// Default maximum age of a key binding token, which is created right before being presented
+pub const DEFAULT_KEY_BINDING_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// How the verifier checks the `exp`, `nbf`, `iat`, `aud` and `iss` claims of an SD-CWT, or of an
+/// SD-JWT, and the `iat` of its key binding token. By default, times are checked against the
+/// system clock, without any skew nor maximum age and with an optional `exp`, while any audience
+/// and issuer is accepted. Key binding tokens must however be at most
+/// [DEFAULT_KEY_BINDING_MAX_AGE] old. A multi-tenant verifier keeps one of these per tenant.
+#[derive(Clone)]
+pub struct VerificationOptions {
+    clock: Arc<dyn Clock>,
+    clock_skew: Duration,
+    max_age: Option<Duration>,
+    key_binding_max_age: Duration,
+    require_exp: bool,
+    audiences: Vec<Accepted>,
+    issuers: Vec<Accepted>,
+}
+
+impl Default for VerificationOptions {
+    fn default() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            clock_skew: Duration::ZERO,
+            max_age: None,
+            key_binding_max_age: DEFAULT_KEY_BINDING_MAX_AGE,
+            require_exp: false,
+            audiences: vec![],
+            issuers: vec![],
+        }
+    }
+}
+
+impl VerificationOptions {
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Tolerated difference between the clocks of the issuer and of the verifier
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Rejects the SD-CWTs issued, according to `iat`, longer ago than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Rejects the key binding tokens issued, according to their `iat`, longer ago than `max_age`
+    pub fn with_key_binding_max_age(mut self, max_age: Duration) -> Self {
+        self.key_binding_max_age = max_age;
+        self
+    }
+
+    /// Rejects the SD-CWTs without an `exp` claim
+    pub fn require_exp(mut self) -> Self {
+        self.require_exp = true;
+        self
+    }
+
+    /// Accepts the SD-CWTs intended for this audience, on top of the already accepted ones
+    pub fn accept_audience(mut self, audience: Accepted) -> Self {
+        self.audiences.push(audience);
+        self
+    }
+
+    /// Accepts the SD-CWTs issued by this issuer, on top of the already accepted ones. Once an
+    /// issuer is accepted, SD-CWTs without an `iss` claim are rejected.
+    pub fn accept_issuer(mut self, issuer: Accepted) -> Self {
+        self.issuers.push(issuer);
+        self
+    }
+
+    /// Checks the registered claims of a claims set, e.g. those of an SD-JWT
+    pub fn validate_claims(&self, claims: &RegisteredClaims) -> SdCwtResult<()> {
+        self.validate_times(claims)?;
+        self.validate_parties(claims)
+    }
+
+    /// Checks that a key binding token issued at `iat` was issued neither in the future nor too
+    /// long ago
+    pub fn validate_key_binding_iat(&self, iat: i64) -> SdCwtResult<()> {
+        let now = self.clock.now()?;
+        self.validate_iat(Some(iat), Some(self.key_binding_max_age), now)
+    }
+
+    pub(crate) fn validate(&self, payload: &SdPayload) -> SdCwtResult<()> {
+        self.validate_claims(&RegisteredClaims::try_from(payload)?)
+    }
+
+    pub(crate) fn validate_key_binding(&self, kbt: &SdPayload) -> SdCwtResult<()> {
+        self.validate_key_binding_iat(claim_time(&kbt.iat, 6)?)
+    }
+
+    fn validate_parties(&self, claims: &RegisteredClaims) -> SdCwtResult<()> {
+        let is_accepted = |accepted: &[Accepted], value: &str| {
+            accepted.is_empty() || accepted.iter().any(|a| a.matches(value))
+        };
+        if !self.audiences.is_empty()
+            && !claims
+                .aud
+                .iter()
+                .any(|aud| is_accepted(&self.audiences, aud))
+        {
+            return Err(SdCwtError::UnacceptedAudience(claims.aud.join(", ")));
+        }
+        if !self.issuers.is_empty() {
+            match claims.iss {
+                Some(iss) if is_accepted(&self.issuers, iss) => {}
+                iss => return Err(SdCwtError::UnacceptedIssuer(iss.map(str::to_string))),
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_times(&self, claims: &RegisteredClaims) -> SdCwtResult<()> {
+        let now = self.clock.now()?;
+        let skew = self.clock_skew.as_secs() as i64;
+
+        match claims.exp {
+            Some(exp) if now >= exp.saturating_add(skew) => {
+                return Err(SdCwtError::Expired { exp, now });
+            }
+            None if self.require_exp => return Err(SdCwtError::MissingExpiration),
+            _ => {}
+        }
+        if let Some(nbf) = claims.nbf {
+            if now.saturating_add(skew) < nbf {
+                return Err(SdCwtError::NotYetValid { nbf, now });
+            }
+        }
+
+        self.validate_iat(claims.iat, self.max_age, now)
+    }
+
+    fn validate_iat(
+        &self,
+        iat: Option<i64>,
+        max_age: Option<Duration>,
+        now: i64,
+    ) -> SdCwtResult<()> {
+        let skew = self.clock_skew.as_secs() as i64;
+        let Some(iat) = iat else {
+            return match max_age {
+                Some(_) => Err(SdCwtError::MissingIssuedAt),
+                None => Ok(()),
+            };
+        };
+        if iat > now.saturating_add(skew) {
+            return Err(SdCwtError::IssuedInFuture { iat, now });
+        }
+        if let Some(max_age) = max_age {
+            let max_age = max_age.as_secs();
+            let age = now.saturating_sub(iat);
+            if age > (max_age as i64).saturating_add(skew) {
+                return Err(SdCwtError::TooOld { iat, max_age });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Claims checked by [VerificationOptions], whatever the encoding of the claims set they come
+/// from. Times are in seconds since the Unix epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisteredClaims<'a> {
+    pub iss: Option<&'a str>,
+    /// Audiences the claims set is intended for, a JWT having possibly several of them
+    pub aud: Vec<&'a str>,
+    pub exp: Option<i64>,
+    pub nbf: Option<i64>,
+    pub iat: Option<i64>,
+}
+
+impl<'a> TryFrom<&'a SdPayload> for RegisteredClaims<'a> {
+    type Error = SdCwtError;
+
+    fn try_from(payload: &'a SdPayload) -> SdCwtResult<Self> {
+        Ok(Self {
+            iss: payload.iss.as_deref(),
+            aud: vec![payload.aud.as_str()],
+            exp: payload
+                .exp
+                .as_ref()
+                .map(|exp| claim_time(exp, 4))
+                .transpose()?,
+            nbf: payload
+                .nbf
+                .as_ref()
+                .map(|nbf| claim_time(nbf, 5))
+                .transpose()?,
+            iat: Some(claim_time(&payload.iat, 6)?),
+        })
+    }
+}
+
+fn claim_time(time: &Int, label: i64) -> SdCwtResult<i64> {
+    int_to_i64(time).ok_or(SdCwtError::InvalidClaim(label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbor::IntoInt;
+    use sd_cwt_types::ordered_hash_map::OrderedHashMap;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn payload(iat: i64, exp: Option<i64>, nbf: Option<i64>) -> SdPayload {
+        let mut payload = SdPayload::new(
+            "https://verifier.example".to_string(),
+            iat.into_int(),
+            OrderedHashMap::new(),
+        );
+        payload.exp = exp.map(IntoInt::into_int);
+        payload.nbf = nbf.map(IntoInt::into_int);
+        payload
+    }
+
+    fn claims(iat: i64, exp: Option<i64>, nbf: Option<i64>) -> RegisteredClaims<'static> {
+        RegisteredClaims {
+            iss: None,
+            aud: vec!["https://verifier.example"],
+            exp,
+            nbf,
+            iat: Some(iat),
+        }
+    }
+
+    fn options() -> VerificationOptions {
+        VerificationOptions::default().with_clock(|| NOW)
+    }
+
+    #[test]
+    fn should_accept_valid_times() {
+        let claims = claims(NOW - 10, Some(NOW + 10), Some(NOW - 10));
+        options().validate_times(&claims).unwrap();
+        options()
+            .require_exp()
+            .with_max_age(Duration::from_secs(10))
+            .validate_times(&claims)
+            .unwrap();
+    }
+
+    #[test]
+    fn should_fail_when_expired() {
+        assert!(matches!(
+            options().validate_times(&claims(NOW - 10, Some(NOW), None)),
+            Err(SdCwtError::Expired { exp: NOW, now: NOW })
+        ));
+        options()
+            .with_clock_skew(Duration::from_secs(5))
+            .validate_times(&claims(NOW - 10, Some(NOW - 4), None))
+            .unwrap();
+    }
+
+    #[test]
+    fn should_fail_when_not_yet_valid() {
+        assert!(matches!(
+            options().validate_times(&claims(NOW, None, Some(NOW + 1))),
+            Err(SdCwtError::NotYetValid { nbf, .. }) if nbf == NOW + 1
+        ));
+        options()
+            .with_clock_skew(Duration::from_secs(1))
+            .validate_times(&claims(NOW, None, Some(NOW + 1)))
+            .unwrap();
+    }
+
+    #[test]
+    fn should_fail_when_issued_in_the_future() {
+        assert!(matches!(
+            options().validate_times(&claims(NOW + 60, None, None)),
+            Err(SdCwtError::IssuedInFuture { iat, now: NOW }) if iat == NOW + 60
+        ));
+    }
+
+    #[test]
+    fn should_fail_when_too_old() {
+        let options = options().with_max_age(Duration::from_secs(3600));
+        options
+            .validate_times(&claims(NOW - 3600, None, None))
+            .unwrap();
+        assert!(matches!(
+            options.validate_times(&claims(NOW - 3601, None, None)),
+            Err(SdCwtError::TooOld { max_age: 3600, .. })
+        ));
+    }
+
+    #[test]
+    fn should_match_patterns() {
+        let pattern = Accepted::pattern("https://*.tenant.example/*");
+        assert!(!pattern.matches("https://a.tenant.example/"));
+        assert!(pattern.matches("https://a.tenant.example/verify"));
+        assert!(!pattern.matches("https://a.b.tenant.example/verify"));
+        assert!(!pattern.matches("https://tenant.example/"));
+        assert!(!pattern.matches("http://a.tenant.example/"));
+        assert!(Accepted::pattern("*").matches("a"));
+        assert!(!Accepted::pattern("*").matches(""));
+        assert!(Accepted::pattern("a").matches("a"));
+        assert!(!Accepted::pattern("a").matches("ab"));
+        assert!(!Accepted::pattern("a*a").matches("a"));
+        assert!(!Accepted::exact("https://*.example").matches("https://a.example"));
+
+        let pattern = Accepted::pattern("https://*.tenant.example");
+        for value in [
+            "https://attacker.com/x.tenant.example",
+            "https://attacker.com:443.tenant.example",
+            "https://user@attacker.com?.tenant.example",
+            "https://attacker.com?q.tenant.example",
+            "https://attacker.com#.tenant.example",
+            "https://attacker.com.tenant.example",
+            "https://.tenant.example",
+        ] {
+            assert!(!pattern.matches(value), "{value}");
+        }
+    }
+
+    #[test]
+    fn should_check_audience_and_issuer() {
+        let mut payload = payload(NOW, None, None);
+        payload.iss = Some("https://issuer.example".to_string());
+
+        let tenant_a = options()
+            .accept_audience(Accepted::exact("https://verifier.example"))
+            .accept_issuer(Accepted::pattern("https://*.example"));
+        let tenant_b = options()
+            .accept_audience(Accepted::pattern("https://*.tenant-b.example"))
+            .accept_issuer(Accepted::exact("https://issuer.example"));
+        tenant_a.validate(&payload).unwrap();
+        assert!(matches!(
+            tenant_b.validate(&payload),
+            Err(SdCwtError::UnacceptedAudience(aud)) if aud == "https://verifier.example"
+        ));
+
+        let tenant_c = options().accept_issuer(Accepted::exact("https://other.example"));
+        assert!(matches!(
+            tenant_c.validate(&payload),
+            Err(SdCwtError::UnacceptedIssuer(Some(iss))) if iss == "https://issuer.example"
+        ));
+        payload.iss = None;
+        assert!(matches!(
+            tenant_a.validate(&payload),
+            Err(SdCwtError::UnacceptedIssuer(None))
+        ));
+        options().validate(&payload).unwrap();
+    }
+
+    #[test]
+    fn should_fail_without_iat_when_checking_age() {
+        let claims = RegisteredClaims {
+            iat: None,
+            ..claims(NOW, None, None)
+        };
+        options().validate_claims(&claims).unwrap();
+        assert!(matches!(
+            options()
+                .with_max_age(Duration::from_secs(60))
+                .validate_claims(&claims),
+            Err(SdCwtError::MissingIssuedAt)
+        ));
+    }
+
+    #[test]
+    fn should_accept_any_of_several_audiences() {
+        let options = options().accept_audience(Accepted::exact("https://verifier.example"));
+        let mut claims = claims(NOW, None, None);
+        claims.aud.insert(0, "https://other.example");
+        options.validate_claims(&claims).unwrap();
+        claims.aud.clear();
+        assert!(matches!(
+            options.validate_claims(&claims),
+            Err(SdCwtError::UnacceptedAudience(aud)) if aud.is_empty()
+        ));
+    }
+
+    #[test]
+    fn should_fail_without_required_exp() {
+        assert!(matches!(
+            options()
+                .require_exp()
+                .validate_times(&claims(NOW, None, None)),
+            Err(SdCwtError::MissingExpiration)
+        ));
+    }
+}
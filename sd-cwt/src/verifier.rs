@@ -0,0 +1,896 @@
+use std::collections::{HashMap, HashSet};
+
+use ciborium::Value as CborValue;
+use jwt_simple::prelude::*;
+use p256::ecdsa::signature::Verifier;
+use sd_cwt_types::{
+    ordered_hash_map::OrderedHashMap,
+    serialization::{Deserialize, ToCBORBytes},
+    Anyy, IntOrText, Keyy, Salted, SdPayload,
+};
+
+use crate::cbor::{self, int_to_i64, sig_structure, IntoInt};
+use crate::cose_key::CoseKey;
+use crate::error::{SdCwtError, SdCwtResult};
+use crate::hash::HashAlgorithm;
+use crate::issuer::{redacted_element_digest, CNF_LABEL, REDACTED_KEYS_LABEL};
+use crate::kbt::{self, KBT_TYP};
+use crate::signing::SigningAlgorithm;
+use crate::types;
+use crate::validation::VerificationOptions;
+use crate::SdCwt;
+
+/// Public key of any supported [SigningAlgorithm]
+#[derive(Clone)]
+pub enum VerifierPublicKey {
+    EdDsa(Ed25519PublicKey),
+    Es256(ES256PublicKey),
+    Es384(ES384PublicKey),
+    Es512(p521::ecdsa::VerifyingKey),
+}
+
+impl From<Ed25519PublicKey> for VerifierPublicKey {
+    fn from(pk: Ed25519PublicKey) -> Self {
+        Self::EdDsa(pk)
+    }
+}
+
+impl From<ES256PublicKey> for VerifierPublicKey {
+    fn from(pk: ES256PublicKey) -> Self {
+        Self::Es256(pk)
+    }
+}
+
+impl From<ES384PublicKey> for VerifierPublicKey {
+    fn from(pk: ES384PublicKey) -> Self {
+        Self::Es384(pk)
+    }
+}
+
+impl From<p521::ecdsa::VerifyingKey> for VerifierPublicKey {
+    fn from(pk: p521::ecdsa::VerifyingKey) -> Self {
+        Self::Es512(pk)
+    }
+}
+
+impl VerifierPublicKey {
+    /// Decodes a raw Ed25519 public key or a SEC1 encoded, compressed or not, ECDSA one
+    pub fn from_bytes(alg: SigningAlgorithm, raw: &[u8]) -> SdCwtResult<Self> {
+        Ok(match alg {
+            SigningAlgorithm::EdDsa => Self::EdDsa(
+                Ed25519PublicKey::from_bytes(raw).map_err(|_| SdCwtError::InvalidPublicKey)?,
+            ),
+            SigningAlgorithm::Es256 => Self::Es256(
+                ES256PublicKey::from_bytes(raw).map_err(|_| SdCwtError::InvalidPublicKey)?,
+            ),
+            SigningAlgorithm::Es384 => Self::Es384(
+                ES384PublicKey::from_bytes(raw).map_err(|_| SdCwtError::InvalidPublicKey)?,
+            ),
+            SigningAlgorithm::Es512 => Self::Es512(
+                p521::ecdsa::VerifyingKey::from_sec1_bytes(raw)
+                    .map_err(|_| SdCwtError::InvalidPublicKey)?,
+            ),
+        })
+    }
+
+    /// Raw Ed25519 public key or compressed SEC1 encoded ECDSA one
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::EdDsa(pk) => pk.to_bytes(),
+            Self::Es256(pk) => pk.to_bytes(),
+            Self::Es384(pk) => pk.to_bytes(),
+            Self::Es512(pk) => pk.to_encoded_point(true).as_bytes().to_vec(),
+        }
+    }
+
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        match self {
+            Self::EdDsa(_) => SigningAlgorithm::EdDsa,
+            Self::Es256(_) => SigningAlgorithm::Es256,
+            Self::Es384(_) => SigningAlgorithm::Es384,
+            Self::Es512(_) => SigningAlgorithm::Es512,
+        }
+    }
+
+    /// Encodes this key as an OKP or EC2 COSE_Key, as expected in the `cnf` claim
+    pub fn to_cose_key(&self) -> CborValue {
+        CoseKey::from(self).to_cbor()
+    }
+
+    /// Decodes an Ed25519 OKP COSE_Key or a P-256, P-384 or P-521 EC2 one
+    pub fn from_cose_key(cose_key: &CborValue) -> SdCwtResult<Self> {
+        Self::try_from(CoseKey::from_cbor(cose_key)?)
+    }
+
+    /// Verifies the issuer signature then matches every disclosure against the redacted keys and
+    /// elements, recursively since disclosed values can themselves have redacted keys or
+    /// elements. Every disclosure must be referenced exactly once. Returns the payload where the
+    /// disclosed claims and elements have been merged back and from which the redacted keys and
+    /// elements have been removed. Disclosed registered claims are set in their own field and no
+    /// disclosed claim may collide with another claim. The `exp`, `nbf` and `iat` claims are
+    /// checked against the system clock.
+    pub fn verify(&self, sd_cwt: &SdCwt) -> SdCwtResult<SdPayload> {
+        self.verify_with(sd_cwt, &VerificationOptions::default())
+    }
+
+    /// Same as [Self::verify] but checks the `exp`, `nbf`, `iat`, `aud` and `iss` claims
+    /// according to `options`
+    pub fn verify_with(
+        &self,
+        sd_cwt: &SdCwt,
+        options: &VerificationOptions,
+    ) -> SdCwtResult<SdPayload> {
+        let sd_cwt = sd_cwt.decode()?;
+        self.verify_signature(&sd_cwt)?;
+
+        let mut payload = sd_cwt.payload;
+        let hash_alg = HashAlgorithm::from_sd_alg(payload.sd_alg.as_ref())?;
+        let mut disclosed = Disclosed::new(sd_cwt.unprotected.sd_claims, hash_alg)?;
+
+        for (_, value) in payload.custom.iter_mut() {
+            disclosed.resolve(value)?;
+        }
+        for digest in payload.redacted_keys.take().unwrap_or_default() {
+            if let Some((name, value)) = disclosed.reveal_claim(&digest)? {
+                insert_disclosed_claim(&mut payload, name, value)?;
+            }
+        }
+        disclosed.finish()?;
+        // disclosed registered claims are checked as well
+        options.validate(&payload)?;
+        Ok(payload)
+    }
+
+    /// Verifies a presentation on behalf of a verifier: the SD-CWT must be signed by this issuer
+    /// key and bound by a key binding token signed with the holder key confirmed in its `cnf`
+    /// claim, for this audience and nonce
+    pub fn verify_presentation(
+        &self,
+        sd_cwt: &SdCwt,
+        audience: &str,
+        cnonce: Option<&[u8]>,
+    ) -> SdCwtResult<SdPayload> {
+        self.verify_presentation_with(sd_cwt, audience, cnonce, &VerificationOptions::default())
+    }
+
+    /// Same as [Self::verify_presentation] but checks the `exp`, `nbf`, `iat`, `aud` and `iss`
+    /// claims of the SD-CWT according to `options`
+    pub fn verify_presentation_with(
+        &self,
+        sd_cwt: &SdCwt,
+        audience: &str,
+        cnonce: Option<&[u8]>,
+        options: &VerificationOptions,
+    ) -> SdCwtResult<SdPayload> {
+        let payload = self.verify_with(sd_cwt, options)?;
+        let presentation = cbor::from_slice(sd_cwt.as_bytes())?;
+        let kbt = kbt::key_binding_token(&presentation)?;
+        let holder_key = kbt::holder_key(&payload)?;
+        holder_key.verify_key_binding_token(sd_cwt, kbt, audience, cnonce, options)?;
+        Ok(payload)
+    }
+
+    /// Verifies, with this holder key, the key binding token of a presentation, which must have
+    /// been issued at most [crate::validation::DEFAULT_KEY_BINDING_MAX_AGE] ago. This does not
+    /// verify the issuer signature, see [Self::verify_presentation] for that.
+    pub fn verify_key_binding(
+        &self,
+        sd_cwt: &SdCwt,
+        audience: &str,
+        cnonce: Option<&[u8]>,
+    ) -> SdCwtResult<()> {
+        self.verify_key_binding_with(sd_cwt, audience, cnonce, &VerificationOptions::default())
+    }
+
+    /// Same as [Self::verify_key_binding] but checks the `iat` of the key binding token
+    /// according to `options`
+    pub fn verify_key_binding_with(
+        &self,
+        sd_cwt: &SdCwt,
+        audience: &str,
+        cnonce: Option<&[u8]>,
+        options: &VerificationOptions,
+    ) -> SdCwtResult<()> {
+        let presentation = cbor::from_slice(sd_cwt.as_bytes())?;
+        let kbt = kbt::key_binding_token(&presentation)?;
+        self.verify_key_binding_token(sd_cwt, kbt, audience, cnonce, options)
+    }
+
+    fn verify_key_binding_token(
+        &self,
+        presentation: &SdCwt,
+        kbt: &[u8],
+        audience: &str,
+        cnonce: Option<&[u8]>,
+        options: &VerificationOptions,
+    ) -> SdCwtResult<()> {
+        let kbt = sd_cwt_types::SdCwtKbt::from_cbor_bytes(kbt)
+            .map_err(|_| SdCwtError::InvalidKeyBinding)?;
+        self.verify_signature(&kbt)?;
+
+        if kbt.protected.typ != KBT_TYP {
+            return Err(SdCwtError::InvalidKeyBinding);
+        }
+        if kbt.payload.aud != audience {
+            return Err(SdCwtError::KeyBindingAudienceMismatch(kbt.payload.aud));
+        }
+        if kbt.payload.cnonce.as_deref() != cnonce {
+            return Err(SdCwtError::KeyBindingNonceMismatch);
+        }
+        if kbt.payload.sd_hash != Some(kbt::presentation_digest(presentation.as_bytes())?) {
+            return Err(SdCwtError::KeyBindingHashMismatch);
+        }
+        options.validate_key_binding(&kbt.payload)
+    }
+
+    fn verify_signature(&self, sd_cwt: &types::SdCwt) -> SdCwtResult<()> {
+        let alg = int_to_i64(&sd_cwt.protected.alg)
+            .ok_or_else(|| {
+                SdCwtError::UnsupportedSigningAlgorithm(sd_cwt.protected.alg.to_string())
+            })
+            .and_then(SigningAlgorithm::try_from)?;
+        if alg != self.algorithm() {
+            return Err(SdCwtError::SigningAlgorithmMismatch {
+                expected: self.algorithm().cose_alg(),
+                found: alg.cose_alg(),
+            });
+        }
+        let tbs = sig_structure(
+            &sd_cwt.protected.to_cbor_bytes(),
+            &sd_cwt.payload.to_cbor_bytes(),
+        )?;
+        self.verify_raw(&tbs, &sd_cwt.signature)
+    }
+
+    /// Verifies a raw signature, `r || s` for ECDSA, over `tbs`
+    pub fn verify_raw(&self, tbs: &[u8], signature: &[u8]) -> SdCwtResult<()> {
+        match self {
+            Self::EdDsa(pk) => {
+                let signature = ed25519_compact::Signature::from_slice(signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)?;
+                pk.public_key()
+                    .as_ref()
+                    .verify(tbs, &signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)
+            }
+            Self::Es256(pk) => {
+                let signature = p256::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)?;
+                pk.public_key()
+                    .as_ref()
+                    .verify(tbs, &signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)
+            }
+            Self::Es384(pk) => {
+                let signature = p384::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)?;
+                pk.public_key()
+                    .as_ref()
+                    .verify(tbs, &signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)
+            }
+            Self::Es512(pk) => {
+                let signature = p521::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)?;
+                pk.verify(tbs, &signature)
+                    .map_err(|_| SdCwtError::InvalidSignature)
+            }
+        }
+    }
+}
+
+/// Adds a disclosed top-level claim to the payload, registered claims going to their typed field.
+/// A claim already present in plaintext or disclosed twice is rejected.
+fn insert_disclosed_claim(
+    payload: &mut SdPayload,
+    name: IntOrText,
+    value: Anyy,
+) -> SdCwtResult<()> {
+    let key = match name {
+        IntOrText::Int(i) => Keyy::new_int(i),
+        IntOrText::Text { text, .. } => Keyy::new_text(text),
+    };
+    let label = match &key {
+        Keyy::Int(i) => int_to_i64(i),
+        Keyy::Text { .. } => None,
+    };
+    let Some(label) = label else {
+        return insert_once(&mut payload.custom, key, value);
+    };
+    let invalid = || SdCwtError::InvalidClaim(label);
+    if matches!(label, 1 | 2 | 4 | 5 | CNF_LABEL | 39) {
+        // the preserved encoding only knows about the fields of the signed payload
+        payload.encodings = None;
+    }
+    match (label, value) {
+        (1, Anyy::Text { text, .. }) => set_once(&mut payload.iss, label, text),
+        (2, Anyy::Text { text, .. }) => set_once(&mut payload.sub, label, text),
+        (4, Anyy::Int(int)) => set_once(&mut payload.exp, label, int),
+        (5, Anyy::Int(int)) => set_once(&mut payload.nbf, label, int),
+        (39, Anyy::Bytes { bytes, .. }) => set_once(&mut payload.cnonce, label, bytes),
+        (CNF_LABEL, Anyy::Map { entries, .. }) => {
+            let mut cnf = OrderedHashMap::new();
+            for (k, v) in entries {
+                let k = match k {
+                    Anyy::Int(i) => Keyy::new_int(i),
+                    Anyy::Text { text, .. } => Keyy::new_text(text),
+                    _ => return Err(invalid()),
+                };
+                insert_once(&mut cnf, k, v)?;
+            }
+            set_once(&mut payload.cnf, label, cnf)
+        }
+        // aud and iat are mandatory hence always in plaintext
+        (3 | 6, _) => Err(SdCwtError::DuplicateClaim(label.to_string())),
+        (1..=6 | CNF_LABEL | 39 | 1113..=REDACTED_KEYS_LABEL, _) => Err(invalid()),
+        (_, value) => insert_once(&mut payload.custom, key, value),
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, label: i64, value: T) -> SdCwtResult<()> {
+    if field.is_some() {
+        return Err(SdCwtError::DuplicateClaim(label.to_string()));
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+fn insert_once(map: &mut OrderedHashMap<Keyy, Anyy>, key: Keyy, value: Anyy) -> SdCwtResult<()> {
+    if map.contains_key(&key) {
+        return Err(SdCwtError::DuplicateClaim(key_name(&key)));
+    }
+    map.insert(key, value);
+    Ok(())
+}
+
+fn key_name(key: &Keyy) -> String {
+    match key {
+        Keyy::Int(i) => i.to_string(),
+        Keyy::Text { text, .. } => text.clone(),
+    }
+}
+
+/// Disclosures of an SD-CWT by digest, each of which must be referenced exactly once from the
+/// payload or from another disclosure
+struct Disclosed {
+    claims: HashMap<Vec<u8>, (IntOrText, Anyy)>,
+    elements: HashMap<Vec<u8>, Anyy>,
+    references: HashSet<Vec<u8>>,
+}
+
+impl Disclosed {
+    fn new(sd_claims: Option<Vec<Salted>>, hash_alg: HashAlgorithm) -> SdCwtResult<Self> {
+        let (mut claims, mut elements) = (HashMap::new(), HashMap::new());
+        for disclosure in sd_claims.unwrap_or_default() {
+            let digest = hash_alg.digest(disclosure.to_cbor_bytes());
+            if claims.contains_key(&digest) || elements.contains_key(&digest) {
+                return Err(SdCwtError::DuplicateDigest(digest));
+            }
+            match disclosure {
+                Salted::SaltedClaim { salted_claim, .. } => {
+                    claims.insert(digest, (salted_claim.index_1, salted_claim.value));
+                }
+                Salted::SaltedElement { salted_element, .. } => {
+                    elements.insert(digest, salted_element.value);
+                }
+            }
+        }
+        Ok(Self {
+            claims,
+            elements,
+            references: HashSet::new(),
+        })
+    }
+
+    fn reference(&mut self, digest: &[u8]) -> SdCwtResult<()> {
+        if !self.references.insert(digest.to_vec()) {
+            return Err(SdCwtError::DuplicateDigest(digest.to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Disclosed claim with this digest, `None` when undisclosed or a decoy
+    fn reveal_claim(&mut self, digest: &[u8]) -> SdCwtResult<Option<(IntOrText, Anyy)>> {
+        self.reference(digest)?;
+        let Some((name, mut value)) = self.claims.remove(digest) else {
+            return Ok(None);
+        };
+        self.resolve(&mut value)?;
+        Ok(Some((name, value)))
+    }
+
+    /// Disclosed array element with this digest, `None` when undisclosed or a decoy
+    fn reveal_element(&mut self, digest: &[u8]) -> SdCwtResult<Option<Anyy>> {
+        self.reference(digest)?;
+        let Some(mut value) = self.elements.remove(digest) else {
+            return Ok(None);
+        };
+        self.resolve(&mut value)?;
+        Ok(Some(value))
+    }
+
+    /// Merges the disclosed claims back into the maps within `value` and the disclosed elements
+    /// back into its arrays, keeping their position, while dropping the undisclosed ones
+    fn resolve(&mut self, value: &mut Anyy) -> SdCwtResult<()> {
+        match value {
+            Anyy::Map { entries, .. } => {
+                let label = Anyy::new_int(REDACTED_KEYS_LABEL.into_int());
+                let digests = match entries.iter().position(|(k, _)| *k == label) {
+                    Some(position) => match entries.remove(position).1 {
+                        Anyy::Array { elements, .. } => elements,
+                        _ => return Err(SdCwtError::InvalidClaim(REDACTED_KEYS_LABEL)),
+                    },
+                    None => vec![],
+                };
+                for (_, value) in entries.iter_mut() {
+                    self.resolve(value)?;
+                }
+                for digest in digests {
+                    let Anyy::Bytes { bytes, .. } = digest else {
+                        return Err(SdCwtError::InvalidClaim(REDACTED_KEYS_LABEL));
+                    };
+                    if let Some((name, value)) = self.reveal_claim(&bytes)? {
+                        let (key, name) = match name {
+                            IntOrText::Int(i) => (Anyy::new_int(i.clone()), i.to_string()),
+                            IntOrText::Text { text, .. } => (Anyy::new_text(text.clone()), text),
+                        };
+                        if entries.iter().any(|(k, _)| *k == key) {
+                            return Err(SdCwtError::DuplicateClaim(name));
+                        }
+                        entries.push((key, value));
+                    }
+                }
+            }
+            Anyy::Array { elements, .. } => {
+                let mut revealed = Vec::with_capacity(elements.len());
+                for mut element in std::mem::take(elements) {
+                    match redacted_element_digest(&element) {
+                        Some(digest) => revealed.extend(self.reveal_element(digest)?),
+                        None => {
+                            self.resolve(&mut element)?;
+                            revealed.push(element);
+                        }
+                    }
+                }
+                *elements = revealed;
+            }
+            Anyy::Tag { value, .. } => self.resolve(value)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Fails when a disclosure has not been referenced
+    fn finish(self) -> SdCwtResult<()> {
+        match self
+            .claims
+            .into_keys()
+            .chain(self.elements.into_keys())
+            .next()
+        {
+            Some(digest) => Err(SdCwtError::UnreferencedDisclosure(digest)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sd_cwt_types::{Int, SaltedClaimItem, SaltedElementItem};
+
+    use super::*;
+    use crate::input::InputClaims;
+    use crate::issuer::{sign_cose_sign1, SD_CWT_TYP};
+    use crate::{ClaimLabels, Decoys, IssuerPrivateKey, SigningKey};
+
+    const YAML: &str = r#"
+        1: https://issuer.example
+        3: https://verifier.example
+        !sd given_name: John
+        !sd family_name: Doe
+        email: "johndoe@example.com"
+    "#;
+
+    fn issue() -> (IssuerPrivateKey, SdCwt) {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_cwt = issuer.sign(YAML.parse::<InputClaims>().unwrap()).unwrap();
+        (issuer, sd_cwt)
+    }
+
+    fn tamper(sd_cwt: &SdCwt, f: impl FnOnce(&mut sd_cwt_types::SdCwt)) -> SdCwt {
+        let mut sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        f(&mut sd_cwt);
+        SdCwt::from(sd_cwt.to_cbor_bytes())
+    }
+
+    /// Re-signs the SD-CWT, with a new issuer key, with an extra top-level disclosure
+    fn with_disclosure(sd_cwt: &SdCwt, name: IntOrText, value: Anyy) -> (VerifierPublicKey, SdCwt) {
+        let mut sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        let salted = SaltedClaimItem::new(vec![0; 16], name, value).unwrap();
+        let salted = Salted::new_salted_claim(salted);
+        let digest = HashAlgorithm::default().digest(salted.to_cbor_bytes());
+        let redacted_keys = sd_cwt.payload.redacted_keys.get_or_insert_with(Vec::new);
+        redacted_keys.push(digest);
+        sd_cwt
+            .unprotected
+            .sd_claims
+            .get_or_insert_with(Vec::new)
+            .push(salted);
+
+        let key = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let sd_cwt = sign_cose_sign1(&key, SD_CWT_TYP, sd_cwt.unprotected, sd_cwt.payload);
+        (key.public_key(), SdCwt::from(sd_cwt.unwrap()))
+    }
+
+    #[test]
+    fn should_verify() {
+        let (issuer, sd_cwt) = issue();
+        let payload = issuer.public_key().verify(&sd_cwt).unwrap();
+
+        assert_eq!(payload.iss.as_deref(), Some("https://issuer.example"));
+        assert_eq!(payload.aud, "https://verifier.example");
+        assert!(payload.redacted_keys.is_none());
+        for (claim, value) in [
+            ("given_name", "John"),
+            ("family_name", "Doe"),
+            ("email", "johndoe@example.com"),
+        ] {
+            assert_eq!(
+                payload.custom.get(&Keyy::new_text(claim.to_string())),
+                Some(&Anyy::new_text(value.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn should_verify_with_sd_alg() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = format!("{YAML}    1114: -65537");
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let presentation = sd_cwt
+            .present(&["/given_name"].map(|p| p.parse().unwrap()))
+            .unwrap();
+
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert_eq!(payload.sd_alg, Some(Int::new_nint(65536)));
+        assert!(payload
+            .custom
+            .contains_key(&Keyy::new_text("given_name".to_string())));
+    }
+
+    #[test]
+    fn should_ignore_decoys() {
+        let issuer = IssuerPrivateKey::generate().with_decoys(Decoys::Range(1..=5));
+        let sd_cwt = issuer.sign(YAML.parse::<InputClaims>().unwrap()).unwrap();
+        let presentation = sd_cwt
+            .present(&["/given_name"].map(|p| p.parse().unwrap()))
+            .unwrap();
+
+        let payload = issuer.public_key().verify(&presentation).unwrap();
+        assert!(payload
+            .custom
+            .contains_key(&Keyy::new_text("given_name".to_string())));
+        assert!(!payload
+            .custom
+            .contains_key(&Keyy::new_text("family_name".to_string())));
+    }
+
+    #[test]
+    fn should_fail_with_unreferenced_element() {
+        let (issuer, sd_cwt) = issue();
+        let sd_cwt = tamper(&sd_cwt, |sd_cwt| {
+            let salted = SaltedElementItem::new(vec![0; 16], Anyy::Bool(true)).unwrap();
+            let sd_claims = sd_cwt.unprotected.sd_claims.as_mut().unwrap();
+            sd_claims.push(Salted::new_salted_element(salted));
+        });
+        assert!(matches!(
+            issuer.public_key().verify(&sd_cwt),
+            Err(SdCwtError::UnreferencedDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn should_verify_nested_disclosures() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd address:
+                !sd street_address: "123 Main St"
+                country: US
+            nationalities:
+                - !sd
+                    !sd code: US
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let payload = issuer.public_key().verify(&sd_cwt).unwrap();
+
+        let claim = |name: &str| payload.custom.get(&Keyy::new_text(name.to_string()));
+        let text = |text: &str| Anyy::new_text(text.to_string());
+        assert_eq!(
+            claim("address"),
+            Some(&Anyy::new_map(vec![
+                (text("country"), text("US")),
+                (text("street_address"), text("123 Main St"))
+            ]))
+        );
+        assert_eq!(
+            claim("nationalities"),
+            Some(&Anyy::new_array(vec![Anyy::new_map(vec![(
+                text("code"),
+                text("US")
+            )])]))
+        );
+    }
+
+    #[test]
+    fn should_fail_when_nested_disclosure_collides_with_plaintext_key() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            address:
+                !sd street_address: "123 Main St"
+                country: US
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let mut sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+
+        let name = IntOrText::new_text("country".to_string());
+        let salted = SaltedClaimItem::new(vec![0; 16], name, Anyy::new_text("FR".into()));
+        let salted = Salted::new_salted_claim(salted.unwrap());
+        let digest = HashAlgorithm::default().digest(salted.to_cbor_bytes());
+        sd_cwt.unprotected.sd_claims.as_mut().unwrap().push(salted);
+        let address = sd_cwt
+            .payload
+            .custom
+            .get_mut(&Keyy::new_text("address".to_string()));
+        let Some(Anyy::Map { entries, .. }) = address else {
+            panic!("address should be a map");
+        };
+        let redacted_keys = Anyy::new_int(REDACTED_KEYS_LABEL.into_int());
+        let (_, Anyy::Array { elements, .. }) = entries
+            .iter_mut()
+            .find(|(k, _)| *k == redacted_keys)
+            .unwrap()
+        else {
+            panic!("redacted keys should be an array");
+        };
+        elements.push(Anyy::new_bytes(digest));
+
+        let key = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let sd_cwt = sign_cose_sign1(&key, SD_CWT_TYP, sd_cwt.unprotected, sd_cwt.payload);
+        assert!(matches!(
+            key.public_key().verify(&SdCwt::from(sd_cwt.unwrap())),
+            Err(SdCwtError::DuplicateClaim(name)) if name == "country"
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_orphan_nested_disclosure() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            !sd address:
+                !sd street_address: "123 Main St"
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        // drop the address disclosure, the street address one cannot be referenced anymore
+        let sd_cwt = tamper(&sd_cwt, |sd_cwt| {
+            let sd_claims = sd_cwt.unprotected.sd_claims.as_mut().unwrap();
+            sd_claims.retain(|d| match d {
+                Salted::SaltedClaim { salted_claim, .. } => !matches!(
+                    &salted_claim.index_1,
+                    IntOrText::Text { text, .. } if text == "address"
+                ),
+                Salted::SaltedElement { .. } => true,
+            });
+        });
+        assert!(matches!(
+            issuer.public_key().verify(&sd_cwt),
+            Err(SdCwtError::UnreferencedDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_digest_referenced_twice() {
+        let (_, sd_cwt) = issue();
+        let mut sd_cwt = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes()).unwrap();
+        let redacted_keys = sd_cwt.payload.redacted_keys.as_mut().unwrap();
+        redacted_keys.push(redacted_keys[0].clone());
+
+        let key = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let sd_cwt = sign_cose_sign1(&key, SD_CWT_TYP, sd_cwt.unprotected, sd_cwt.payload);
+        assert!(matches!(
+            key.public_key().verify(&SdCwt::from(sd_cwt.unwrap())),
+            Err(SdCwtError::DuplicateDigest(_))
+        ));
+    }
+
+    #[test]
+    fn should_display_verified_claims_by_name() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            iss: https://issuer.example
+            aud: https://verifier.example
+            iat: 1725244200
+            !sd sub: user_42
+        "#;
+        let labels = ClaimLabels::default();
+        let input = yaml.parse::<InputClaims>().unwrap().with_labels(&labels);
+        let sd_cwt = issuer.sign(input.unwrap()).unwrap();
+
+        let payload = issuer.public_key().verify(&sd_cwt).unwrap();
+        assert_eq!(payload.iss.as_deref(), Some("https://issuer.example"));
+        let claims = labels.named_claims(&payload).unwrap();
+        let claims = claims.as_map().unwrap();
+        let claim = |name: &str| {
+            claims
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(name))
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(claim("aud"), Some("https://verifier.example".into()));
+        assert_eq!(claim("iat"), Some(1725244200.into()));
+        assert_eq!(claim("sub"), Some("user_42".into()));
+    }
+
+    #[test]
+    fn should_check_validity_period() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            3: https://verifier.example
+            4: 1700003600
+            5: 1700000000
+            6: 1700000000
+        "#;
+        let sd_cwt = issuer.sign(yaml.parse::<InputClaims>().unwrap()).unwrap();
+        let verifier = issuer.public_key();
+        let at = |now: i64| VerificationOptions::default().with_clock(move || now);
+
+        verifier.verify_with(&sd_cwt, &at(1700000000)).unwrap();
+        assert!(matches!(
+            verifier.verify_with(&sd_cwt, &at(1699999999)),
+            Err(SdCwtError::NotYetValid { .. })
+        ));
+        assert!(matches!(
+            verifier.verify_with(&sd_cwt, &at(1700003600)),
+            Err(SdCwtError::Expired { .. })
+        ));
+        // with the system clock
+        assert!(matches!(
+            verifier.verify(&sd_cwt),
+            Err(SdCwtError::Expired { .. })
+        ));
+    }
+
+    #[test]
+    fn should_verify_from_raw_key() {
+        let (issuer, sd_cwt) = issue();
+        let verifier =
+            VerifierPublicKey::from_bytes(SigningAlgorithm::EdDsa, &issuer.public_key().to_bytes())
+                .unwrap();
+        assert!(verifier.verify(&sd_cwt).is_ok());
+    }
+
+    #[test]
+    fn should_verify_ecdsa() {
+        for alg in [
+            SigningAlgorithm::Es256,
+            SigningAlgorithm::Es384,
+            SigningAlgorithm::Es512,
+        ] {
+            let issuer = IssuerPrivateKey::generate_with_algorithm(alg);
+            let sd_cwt = issuer.sign(YAML.parse::<InputClaims>().unwrap()).unwrap();
+            let protected = sd_cwt_types::SdCwt::from_cbor_bytes(sd_cwt.as_bytes())
+                .unwrap()
+                .protected;
+            assert_eq!(int_to_i64(&protected.alg), Some(alg.cose_alg()));
+
+            let verifier = VerifierPublicKey::from_bytes(alg, &issuer.public_key().to_bytes());
+            assert!(verifier.unwrap().verify(&sd_cwt).is_ok());
+        }
+    }
+
+    #[test]
+    fn should_fail_with_key_of_another_algorithm() {
+        let (_, sd_cwt) = issue();
+        let verifier = IssuerPrivateKey::generate_with_algorithm(SigningAlgorithm::Es256);
+        assert!(matches!(
+            verifier.public_key().verify(&sd_cwt),
+            Err(SdCwtError::SigningAlgorithmMismatch {
+                expected: -7,
+                found: -8
+            })
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_wrong_key() {
+        let (_, sd_cwt) = issue();
+        let verifier = IssuerPrivateKey::generate().public_key();
+        assert!(matches!(
+            verifier.verify(&sd_cwt),
+            Err(SdCwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn should_fail_when_payload_tampered() {
+        let (issuer, sd_cwt) = issue();
+        let sd_cwt = tamper(&sd_cwt, |sd_cwt| {
+            sd_cwt.payload.aud = "https://evil.example".into()
+        });
+        assert!(matches!(
+            issuer.public_key().verify(&sd_cwt),
+            Err(SdCwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_unreferenced_disclosure() {
+        let (issuer, sd_cwt) = issue();
+        let sd_cwt = tamper(&sd_cwt, |sd_cwt| {
+            let salted = SaltedClaimItem::new(
+                vec![0; 16],
+                IntOrText::new_text("admin".to_string()),
+                Anyy::Bool(true),
+            )
+            .unwrap();
+            let sd_claims = sd_cwt.unprotected.sd_claims.as_mut().unwrap();
+            sd_claims.push(Salted::new_salted_claim(salted));
+        });
+        assert!(matches!(
+            issuer.public_key().verify(&sd_cwt),
+            Err(SdCwtError::UnreferencedDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_duplicate_disclosure() {
+        let (issuer, sd_cwt) = issue();
+        let sd_cwt = tamper(&sd_cwt, |sd_cwt| {
+            let sd_claims = sd_cwt.unprotected.sd_claims.as_mut().unwrap();
+            sd_claims.push(sd_claims[0].clone());
+        });
+        assert!(matches!(
+            issuer.public_key().verify(&sd_cwt),
+            Err(SdCwtError::DuplicateDigest(_))
+        ));
+    }
+
+    #[test]
+    fn should_fail_when_disclosure_collides_with_plaintext_claim() {
+        let (_, sd_cwt) = issue();
+        let email = IntOrText::new_text("email".to_string());
+        let (verifier, sd_cwt) = with_disclosure(&sd_cwt, email, Anyy::Bool(true));
+        assert!(matches!(
+            verifier.verify(&sd_cwt),
+            Err(SdCwtError::DuplicateClaim(name)) if name == "email"
+        ));
+    }
+
+    #[test]
+    fn should_disclose_registered_claims_in_their_field() {
+        let (_, sd_cwt) = issue();
+        let text = |text: &str| Anyy::new_text(text.to_string());
+
+        let (verifier, disclosed) =
+            with_disclosure(&sd_cwt, IntOrText::new_int(2.into_int()), text("sub"));
+        let payload = verifier.verify(&disclosed).unwrap();
+        assert_eq!(payload.sub.as_deref(), Some("sub"));
+        assert!(payload.custom.get(&Keyy::new_int(2.into_int())).is_none());
+
+        // iss is already in plaintext
+        let (verifier, disclosed) =
+            with_disclosure(&sd_cwt, IntOrText::new_int(1.into_int()), text("iss"));
+        assert!(matches!(
+            verifier.verify(&disclosed),
+            Err(SdCwtError::DuplicateClaim(name)) if name == "1"
+        ));
+        let (verifier, disclosed) =
+            with_disclosure(&sd_cwt, IntOrText::new_int(4.into_int()), text("exp"));
+        assert!(matches!(
+            verifier.verify(&disclosed),
+            Err(SdCwtError::InvalidClaim(4))
+        ));
+    }
+}
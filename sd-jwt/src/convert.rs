@@ -0,0 +1,475 @@
+//! Conversion of an unsigned claims set, along with its disclosure plan, between the SD-JWT and
+//! the SD-CWT forms. The disclosable claims of an object, whose digests end up in its `_sd` array
+//! once signed as an SD-JWT, are the ones whose digests end up in the `redacted_keys` of the map
+//! once signed as an SD-CWT, hence the disclosable paths carry over with their first segment
+//! relabeled.
+
+use ciborium::Value as CborValue;
+use sd_cwt::cose_key::{Confirmation, CoseKey};
+use sd_cwt::edn::{EdnClaims, TO_BE_REDACTED_TAG};
+use sd_cwt::input::InputClaims;
+use sd_cwt::issuer::SD_ALG_LABEL;
+use sd_cwt::{ClaimLabels, ClaimPath, HashAlgorithm, PathSegment};
+use serde_json::{Map, Number, Value as JsonValue};
+
+use crate::disclosure::{
+    hash_algorithm, is_key, sd_alg_name, REDACTED_ELEMENT_KEY, SD_ALG_KEY, SD_KEY,
+};
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::jwk::{from_jwk, to_jwk, CNF_JWK_KEY};
+
+/// SD-CWT issuance request for the SD-JWT claims set, where the claims known to `labels` are
+/// replaced by their CWT label, `_sd_alg` by `sd_alg` and a `cnf` JWK by a COSE_Key
+pub fn to_sd_cwt(claims: &InputClaims, labels: &ClaimLabels) -> SdJwtResult<EdnClaims> {
+    let json = claims.to_json()?;
+    let entries = json.as_object().ok_or(SdJwtError::InvalidClaimsSet)?;
+    // the hash algorithm of the disclosures cannot be redacted itself
+    if claims.disclosable_paths.iter().any(|path| {
+        path.segments()
+            .first()
+            .is_some_and(|s| is_key(s, SD_ALG_KEY))
+    }) {
+        return Err(SdJwtError::ReservedClaimName(SD_ALG_KEY.to_string()));
+    }
+
+    let mut cbor = Vec::with_capacity(entries.len());
+    for (name, value) in entries {
+        reserved(name)?;
+        let path = ClaimPath::root().join(name.as_str());
+        let entry = match (name.as_str(), labels.label(name)) {
+            (SD_ALG_KEY, _) => {
+                let hash_alg = hash_algorithm(Some(value))?;
+                (SD_ALG_LABEL.into(), hash_alg.cose_alg().into())
+            }
+            ("cnf", Some(label)) if value.get(CNF_JWK_KEY).is_some() => {
+                let cose_key = CoseKey::from(&from_jwk(&value[CNF_JWK_KEY])?);
+                (label.into(), Confirmation::CoseKey(cose_key).to_cbor())
+            }
+            (_, Some(label)) => (label.into(), json_to_cbor(&path, value)?),
+            (_, None) => (name.as_str().into(), json_to_cbor(&path, value)?),
+        };
+        cbor.push(entry);
+    }
+
+    let disclosable_paths = claims
+        .disclosable_paths
+        .iter()
+        .map(|path| {
+            relabel(path, |segment| match segment {
+                PathSegment::Text(name) => labels.label(name).map(PathSegment::Int),
+                PathSegment::Int(_) => None,
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut raw = CborValue::Map(cbor);
+    for path in &disclosable_paths {
+        mark_to_be_redacted(&mut raw, path.segments())
+            .ok_or_else(|| SdJwtError::InvalidDisclosablePath(path.clone()))?;
+    }
+    Ok(EdnClaims {
+        raw,
+        disclosable_paths,
+    })
+}
+
+/// SD-JWT claims set for the SD-CWT issuance request, where the labels known to `labels` are
+/// replaced by their claim name, `sd_alg` by `_sd_alg` and a `cnf` COSE_Key by a JWK. Fails on
+/// the values without any JSON form e.g. byte strings, tags or integer map keys.
+pub fn to_sd_jwt(claims: &EdnClaims, labels: &ClaimLabels) -> SdJwtResult<InputClaims> {
+    let cbor = claims.to_cbor();
+    let entries = cbor.as_map().ok_or(SdJwtError::InvalidClaimsSet)?;
+    // the hash algorithm of the disclosures cannot be redacted itself
+    if claims
+        .disclosable_paths
+        .iter()
+        .any(|path| path.segments().first() == Some(&PathSegment::Int(SD_ALG_LABEL)))
+    {
+        return Err(SdJwtError::ReservedClaimName(SD_ALG_KEY.to_string()));
+    }
+
+    let mut json = Map::with_capacity(entries.len());
+    for (key, value) in entries {
+        let segment = cbor_key_segment(&ClaimPath::root(), key)?;
+        let path = ClaimPath::root().join(segment.clone());
+        let (name, value) = match segment {
+            PathSegment::Int(SD_ALG_LABEL) => {
+                let hash_alg = cbor_int(value)
+                    .and_then(|alg| HashAlgorithm::try_from(alg).ok())
+                    .ok_or_else(|| no_json_form(&path, "unsupported hash algorithm"))?;
+                (SD_ALG_KEY.to_string(), sd_alg_name(hash_alg).into())
+            }
+            PathSegment::Int(label) if labels.name(label) == Some("cnf") => {
+                let cose_key = match Confirmation::from_cbor(value)? {
+                    Confirmation::CoseKey(cose_key) => cose_key,
+                    _ => return Err(no_json_form(&path, "confirmation other than a COSE_Key")),
+                };
+                let jwk = to_jwk(&sd_cwt::VerifierPublicKey::try_from(&cose_key)?);
+                ("cnf".to_string(), serde_json::json!({ CNF_JWK_KEY: jwk }))
+            }
+            PathSegment::Int(label) => {
+                let name = labels
+                    .name(label)
+                    .ok_or_else(|| no_json_form(&path, "label without claim name"))?;
+                (name.to_string(), cbor_to_json(&path, value)?)
+            }
+            PathSegment::Text(name) => (name, cbor_to_json(&path, value)?),
+        };
+        reserved(&name)?;
+        if json.contains_key(&name) {
+            return Err(SdJwtError::DuplicateClaim(name));
+        }
+        json.insert(name, value);
+    }
+
+    let disclosable_paths = claims
+        .disclosable_paths
+        .iter()
+        .map(|path| {
+            relabel(path, |segment| match segment {
+                PathSegment::Int(label) => labels.name(*label).map(PathSegment::from),
+                PathSegment::Text(_) => None,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(InputClaims::from_json(
+        &JsonValue::Object(json),
+        &disclosable_paths,
+    )?)
+}
+
+fn json_to_cbor(path: &ClaimPath, value: &JsonValue) -> SdJwtResult<CborValue> {
+    Ok(match value {
+        JsonValue::Null => CborValue::Null,
+        JsonValue::Bool(b) => CborValue::Bool(*b),
+        JsonValue::Number(n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+            (Some(u), _, _) => u.into(),
+            (_, Some(i), _) => i.into(),
+            (_, _, Some(f)) => CborValue::Float(f),
+            _ => return Err(SdJwtError::InvalidClaim(path.to_string())),
+        },
+        JsonValue::String(s) => CborValue::Text(s.clone()),
+        JsonValue::Array(elements) => CborValue::Array(
+            elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| json_to_cbor(&path.join(i), element))
+                .collect::<SdJwtResult<_>>()?,
+        ),
+        JsonValue::Object(entries) => CborValue::Map(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    reserved(key)?;
+                    let value = json_to_cbor(&path.join(key.as_str()), value)?;
+                    Ok((CborValue::Text(key.clone()), value))
+                })
+                .collect::<SdJwtResult<_>>()?,
+        ),
+    })
+}
+
+fn cbor_to_json(path: &ClaimPath, value: &CborValue) -> SdJwtResult<JsonValue> {
+    Ok(match value {
+        CborValue::Null => JsonValue::Null,
+        CborValue::Bool(b) => JsonValue::Bool(*b),
+        CborValue::Integer(i) => {
+            let i = i128::from(*i);
+            match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => i.into(),
+                (_, Ok(u)) => u.into(),
+                _ => return Err(no_json_form(path, "integer out of the 64-bit range")),
+            }
+        }
+        CborValue::Float(f) => Number::from_f64(*f)
+            .map(JsonValue::Number)
+            .ok_or_else(|| no_json_form(path, "NaN or infinite float"))?,
+        CborValue::Text(text) => JsonValue::String(text.clone()),
+        CborValue::Bytes(_) => return Err(no_json_form(path, "byte string")),
+        CborValue::Tag(tag, _) => {
+            return Err(SdJwtError::NoJsonForm {
+                path: path.clone(),
+                reason: format!("tag {tag}"),
+            })
+        }
+        CborValue::Array(elements) => JsonValue::Array(
+            elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| cbor_to_json(&path.join(i), element))
+                .collect::<SdJwtResult<_>>()?,
+        ),
+        CborValue::Map(entries) => JsonValue::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match cbor_key_segment(path, key)? {
+                        PathSegment::Text(key) => key,
+                        segment => return Err(no_json_form(&path.join(segment), "integer key")),
+                    };
+                    reserved(&key)?;
+                    let value = cbor_to_json(&path.join(key.as_str()), value)?;
+                    Ok((key, value))
+                })
+                .collect::<SdJwtResult<_>>()?,
+        ),
+        _ => return Err(no_json_form(path, "simple value")),
+    })
+}
+
+fn cbor_key_segment(parent: &ClaimPath, key: &CborValue) -> SdJwtResult<PathSegment> {
+    match key {
+        CborValue::Text(text) => Ok(PathSegment::Text(text.clone())),
+        CborValue::Integer(i) => i64::try_from(*i)
+            .map(PathSegment::Int)
+            .map_err(|_| no_json_form(parent, "integer key out of the 64-bit range")),
+        _ => Err(no_json_form(parent, "key neither integer nor text")),
+    }
+}
+
+/// Claim names carrying the selective disclosure structure of a signed SD-JWT
+fn reserved(name: &str) -> SdJwtResult<()> {
+    if name == SD_KEY || name == REDACTED_ELEMENT_KEY {
+        return Err(SdJwtError::ReservedClaimName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn no_json_form(path: &ClaimPath, reason: &str) -> SdJwtError {
+    SdJwtError::NoJsonForm {
+        path: path.clone(),
+        reason: reason.to_string(),
+    }
+}
+
+fn cbor_int(value: &CborValue) -> Option<i64> {
+    value.as_integer().and_then(|i| i64::try_from(i).ok())
+}
+
+/// Replaces the first segment of the path, the top-level claim, when `f` maps it
+fn relabel(path: &ClaimPath, f: impl Fn(&PathSegment) -> Option<PathSegment>) -> ClaimPath {
+    let mut segments = path.segments().to_vec();
+    if let Some(segment) = segments.first().and_then(f) {
+        segments[0] = segment;
+    }
+    ClaimPath::from(segments)
+}
+
+/// Tags the key or the array element designated by the path as to be redacted, as if it was
+/// written in EDN
+fn mark_to_be_redacted(raw: &mut CborValue, segments: &[PathSegment]) -> Option<()> {
+    let (name, parents) = segments.split_last()?;
+    let parent = parents
+        .iter()
+        .try_fold(raw, |parent, segment| match parent {
+            CborValue::Map(entries) => entries
+                .iter_mut()
+                .find_map(|(k, v)| is_cbor_key(segment, k).then_some(v)),
+            CborValue::Array(elements) => segment.index().and_then(|i| elements.get_mut(i)),
+            _ => None,
+        })?;
+    match parent {
+        CborValue::Map(entries) => {
+            let (key, _) = entries.iter_mut().find(|(k, _)| is_cbor_key(name, k))?;
+            if !matches!(key, CborValue::Tag(TO_BE_REDACTED_TAG, _)) {
+                *key = CborValue::Tag(TO_BE_REDACTED_TAG, Box::new(key.clone()));
+            }
+        }
+        CborValue::Array(elements) => {
+            let element = elements.get_mut(name.index()?)?;
+            if !matches!(element, CborValue::Tag(TO_BE_REDACTED_TAG, _)) {
+                *element = CborValue::Tag(TO_BE_REDACTED_TAG, Box::new(element.clone()));
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Whether the segment designates the map key, whether or not it is already tagged. Text keys
+/// coming from JSON are matched like object keys.
+fn is_cbor_key(segment: &PathSegment, key: &CborValue) -> bool {
+    match key {
+        CborValue::Tag(TO_BE_REDACTED_TAG, key) => is_cbor_key(segment, key),
+        CborValue::Text(key) => is_key(segment, key),
+        key => segment.is_key(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sd_cwt::issuer::CNF_LABEL;
+    use sd_cwt::SigningKey;
+
+    const YAML: &str = r#"
+        iss: https://issuer.example
+        aud: https://verifier.example
+        _sd_alg: sha-384
+        !sd given_name: John
+        !sd address:
+            !sd street_address: "123 Main St"
+            country: US
+        nationalities:
+            - !sd US
+            - DE
+        score: 1.5
+    "#;
+
+    fn converted(edn: &str) -> SdJwtResult<InputClaims> {
+        to_sd_jwt(&edn.parse().unwrap(), &ClaimLabels::default())
+    }
+
+    #[test]
+    fn should_round_trip() {
+        let labels = ClaimLabels::default();
+        let input = YAML.parse::<InputClaims>().unwrap();
+        let sd_cwt_claims = to_sd_cwt(&input, &labels).unwrap();
+
+        let cbor = sd_cwt_claims.to_cbor();
+        let entries = cbor.as_map().unwrap();
+        assert_eq!(entries[0], (1.into(), "https://issuer.example".into()));
+        assert_eq!(entries[2], (SD_ALG_LABEL.into(), (-43).into()));
+        assert_eq!(
+            sd_cwt_claims.disclosable_paths,
+            [
+                "/given_name",
+                "/address",
+                "/address/street_address",
+                "/nationalities/0"
+            ]
+        );
+        // tagged as if written in EDN
+        let raw = sd_cwt_claims.raw.as_map().unwrap();
+        let given_name = CborValue::Tag(TO_BE_REDACTED_TAG, Box::new("given_name".into()));
+        assert!(raw.iter().any(|(k, _)| *k == given_name));
+
+        let back = to_sd_jwt(&sd_cwt_claims, &labels).unwrap();
+        assert_eq!(back.to_json().unwrap(), input.to_json().unwrap());
+        assert_eq!(back.disclosable_paths, input.disclosable_paths);
+    }
+
+    #[test]
+    fn should_issue_both_formats() {
+        let input = YAML.parse::<InputClaims>().unwrap();
+        let issuer = sd_cwt::IssuerPrivateKey::generate();
+        let sd_cwt_claims = to_sd_cwt(&input, &ClaimLabels::default()).unwrap();
+        let sd_cwt = issuer.sign_edn(sd_cwt_claims).unwrap();
+        issuer.public_key().verify(&sd_cwt).unwrap();
+        assert_eq!(sd_cwt.disclosures().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn should_convert_cnf() {
+        let holder_key = SigningKey::generate(sd_cwt::SigningAlgorithm::Es256).public_key();
+        let mut json = input_json();
+        json["cnf"] = serde_json::json!({ CNF_JWK_KEY: to_jwk(&holder_key) });
+        let input = InputClaims::from_json(&json, &[]).unwrap();
+
+        let sd_cwt_claims = to_sd_cwt(&input, &ClaimLabels::default()).unwrap();
+        let cbor = sd_cwt_claims.to_cbor();
+        let (_, cnf) = cbor
+            .as_map()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| *k == CNF_LABEL.into())
+            .unwrap();
+        let cose_key = Confirmation::from_cbor(cnf).unwrap();
+        assert_eq!(cose_key, Confirmation::CoseKey(CoseKey::from(&holder_key)));
+
+        let back = to_sd_jwt(&sd_cwt_claims, &ClaimLabels::default()).unwrap();
+        assert_eq!(back.to_json().unwrap(), json);
+
+        // without any label for `cnf`, it is converted in neither direction
+        let labels = ClaimLabels::default().with_label("holder", CNF_LABEL);
+        assert!(matches!(
+            to_sd_jwt(&sd_cwt_claims, &labels),
+            Err(SdJwtError::NoJsonForm { .. })
+        ));
+        let sd_cwt_claims = to_sd_cwt(&input, &labels).unwrap();
+        let back = to_sd_jwt(&sd_cwt_claims, &labels).unwrap();
+        assert_eq!(back.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn should_fail_with_cbor_only_values() {
+        for (edn, path, reason) in [
+            (r#"{ 3: "aud", "blob": h'01' }"#, "/blob", "byte string"),
+            (r#"{ 3: "aud", "t": [1(1700000000)] }"#, "/t/0", "tag 1"),
+            (r#"{ 3: "aud", "m": { 1: 2 } }"#, "/m/1", "integer key"),
+            (
+                r#"{ 3: "aud", 500: "x" }"#,
+                "/500",
+                "label without claim name",
+            ),
+            (r#"{ 3: "aud", "f": NaN }"#, "/f", "NaN or infinite float"),
+        ] {
+            assert!(matches!(
+                converted(edn),
+                Err(SdJwtError::NoJsonForm { path: p, reason: r }) if p == path && r == reason
+            ));
+        }
+        let labels = ClaimLabels::default().with_label("tenant", 500);
+        let input = to_sd_jwt(&r#"{ 3: "aud", 500: "x" }"#.parse().unwrap(), &labels).unwrap();
+        assert_eq!(input.to_json().unwrap()["tenant"], "x");
+    }
+
+    #[test]
+    fn should_fail_with_reserved_names() {
+        let mut json = input_json();
+        json["address"] = serde_json::json!({ SD_KEY: [] });
+        let input = InputClaims::from_json(&json, &[]).unwrap();
+        assert!(matches!(
+            to_sd_cwt(&input, &ClaimLabels::default()),
+            Err(SdJwtError::ReservedClaimName(name)) if name == SD_KEY
+        ));
+
+        let input = "aud: https://verifier.example\n!sd _sd_alg: sha-384"
+            .parse::<InputClaims>()
+            .unwrap();
+        assert!(matches!(
+            to_sd_cwt(&input, &ClaimLabels::default()),
+            Err(SdJwtError::ReservedClaimName(name)) if name == SD_ALG_KEY
+        ));
+        let edn = r#"{ 3: "aud", 58(1114): -16 }"#;
+        assert!(matches!(
+            converted(edn),
+            Err(SdJwtError::ReservedClaimName(name)) if name == SD_ALG_KEY
+        ));
+
+        // top-level claims as well
+        for name in [SD_KEY, REDACTED_ELEMENT_KEY] {
+            let mut json = input_json();
+            json[name] = 1.into();
+            let input = InputClaims::from_json(&json, &[]).unwrap();
+            assert!(matches!(
+                to_sd_cwt(&input, &ClaimLabels::default()),
+                Err(SdJwtError::ReservedClaimName(n)) if n == name
+            ));
+            let edn = format!(r#"{{ 3: "aud", "{name}": 1 }}"#);
+            assert!(matches!(
+                converted(&edn),
+                Err(SdJwtError::ReservedClaimName(n)) if n == name
+            ));
+        }
+    }
+
+    #[test]
+    fn should_fail_with_duplicate_names() {
+        for (edn, name) in [
+            (r#"{ 3: "aud", "aud": "other" }"#, "aud"),
+            (
+                r#"{ 3: "aud", 1114: -16, "_sd_alg": "sha-256" }"#,
+                SD_ALG_KEY,
+            ),
+        ] {
+            assert!(matches!(
+                converted(edn),
+                Err(SdJwtError::DuplicateClaim(n)) if n == name
+            ));
+        }
+    }
+
+    fn input_json() -> JsonValue {
+        serde_json::json!({ "aud": "https://verifier.example" })
+    }
+}
@@ -0,0 +1,148 @@
+use sd_cwt::{ClaimPath, HashAlgorithm, PathSegment};
+use serde_json::Value as JsonValue;
+
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::jws::{b64, b64_decode};
+
+/// Key of the array holding the digests of the redacted claims of an object
+pub const SD_KEY: &str = "_sd";
+/// Claim advertising the hash algorithm of the disclosures digests
+pub const SD_ALG_KEY: &str = "_sd_alg";
+/// Key of the single entry of a redacted array element, holding the digest of its disclosure
+pub const REDACTED_ELEMENT_KEY: &str = "...";
+
+/// Salted claim `[salt, name, value]` or array element `[salt, value]`, as carried in an SD-JWT
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disclosure {
+    /// Path of the disclosed claim or element in the issued claims set. The path of an array
+    /// element is its index within the issued array, which might include decoys.
+    pub path: ClaimPath,
+    /// Base64url encoding of the JSON array, as hashed
+    pub encoded: String,
+    /// Claim name, `None` for an array element
+    pub name: Option<String>,
+    pub value: JsonValue,
+}
+
+/// Encodes a freshly salted disclosure of a claim, or of an array element when `name` is `None`
+pub(crate) fn encode(name: Option<&str>, value: JsonValue) -> SdJwtResult<String> {
+    let salt = JsonValue::from(b64(rand::random::<[u8; 16]>()));
+    let disclosure = match name {
+        Some(name) => vec![salt, name.into(), value],
+        None => vec![salt, value],
+    };
+    Ok(b64(serde_json::to_vec(&disclosure)?))
+}
+
+/// Decodes the claim name, if any, and the value of a disclosure
+pub(crate) fn decode(encoded: &str) -> SdJwtResult<(Option<String>, JsonValue)> {
+    let invalid = || SdJwtError::InvalidDisclosure(encoded.to_string());
+    let disclosure = serde_json::from_slice::<JsonValue>(&b64_decode(encoded)?)?;
+    match disclosure.as_array().map(Vec::as_slice) {
+        Some([JsonValue::String(_), value]) => Ok((None, value.clone())),
+        Some([JsonValue::String(_), JsonValue::String(name), value]) => {
+            if name == SD_KEY || name == REDACTED_ELEMENT_KEY {
+                return Err(SdJwtError::ReservedClaimName(name.clone()));
+            }
+            Ok((Some(name.clone()), value.clone()))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Base64url encoded digest of the ASCII bytes of an encoded disclosure
+pub(crate) fn digest(hash_alg: HashAlgorithm, encoded: &str) -> String {
+    b64(hash_alg.digest(encoded.as_bytes()))
+}
+
+/// IANA name of a hash algorithm, as advertised in `_sd_alg`
+pub(crate) fn sd_alg_name(hash_alg: HashAlgorithm) -> &'static str {
+    match hash_alg {
+        HashAlgorithm::Sha256 => "sha-256",
+        HashAlgorithm::Sha384 => "sha-384",
+        HashAlgorithm::Sha512 => "sha-512",
+        HashAlgorithm::Sha3_256 => "sha3-256",
+    }
+}
+
+/// Algorithm advertised by `_sd_alg`, SHA-256 when absent
+pub(crate) fn hash_algorithm(sd_alg: Option<&JsonValue>) -> SdJwtResult<HashAlgorithm> {
+    let Some(sd_alg) = sd_alg else {
+        return Ok(HashAlgorithm::default());
+    };
+    [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha384,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Sha3_256,
+    ]
+    .into_iter()
+    .find(|alg| *sd_alg == sd_alg_name(*alg))
+    .ok_or_else(|| SdJwtError::UnsupportedHashAlgorithm(sd_alg.to_string()))
+}
+
+/// Whether the path segment designates the object key. Object keys being text, an integer
+/// segment designates the key spelling it, as in a JSON pointer.
+pub(crate) fn is_key(segment: &PathSegment, key: &str) -> bool {
+    match segment {
+        PathSegment::Text(text) => text == key,
+        PathSegment::Int(i) => i.to_string() == key,
+    }
+}
+
+/// Whether `path` goes through `ancestor`, or is the same, where an integer segment and a text
+/// segment spelling it designate the same object key
+pub(crate) fn starts_with(path: &ClaimPath, ancestor: &ClaimPath) -> bool {
+    path.len() >= ancestor.len()
+        && ancestor
+            .segments()
+            .iter()
+            .zip(path.segments())
+            .all(|(a, b)| match (a, b) {
+                (PathSegment::Text(key), segment) | (segment, PathSegment::Text(key)) => {
+                    is_key(segment, key)
+                }
+                (a, b) => a == b,
+            })
+}
+
+/// Digest carried by a redacted array element, if `value` is one
+pub(crate) fn redacted_element_digest(value: &JsonValue) -> Option<&str> {
+    let entries = value.as_object().filter(|entries| entries.len() == 1)?;
+    entries.get(REDACTED_ELEMENT_KEY)?.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_digest_rfc_example() {
+        // disclosure of the `family_name` claim of RFC 9901
+        let encoded = "WyJfMjZiYzRMVC1hYzZxMktJNmNCVzVlcyIsICJmYW1pbHlfbmFtZSIsICJNw7ZiaXVzIl0";
+        assert_eq!(
+            digest(HashAlgorithm::Sha256, encoded),
+            "X9yH0Ajrdm1Oij4tWso9UzzKJvPoDxwmuEcO3XAdRC0"
+        );
+        let (name, value) = decode(encoded).unwrap();
+        assert_eq!(name.as_deref(), Some("family_name"));
+        assert_eq!(value, "Möbius");
+    }
+
+    #[test]
+    fn should_round_trip() {
+        let encoded = encode(None, "DE".into()).unwrap();
+        assert_eq!(decode(&encoded).unwrap(), (None, "DE".into()));
+
+        let reserved = encode(Some(SD_KEY), JsonValue::Null).unwrap();
+        assert!(matches!(
+            decode(&reserved),
+            Err(SdJwtError::ReservedClaimName(name)) if name == SD_KEY
+        ));
+        for alg in ["sha-256", "sha-384", "sha-512", "sha3-256"] {
+            let hash_alg = hash_algorithm(Some(&alg.into())).unwrap();
+            assert_eq!(sd_alg_name(hash_alg), alg);
+        }
+        assert!(hash_algorithm(Some(&"md5".into())).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use sd_cwt::error::SdCwtError;
+use sd_cwt::ClaimPath;
+
+pub type SdJwtResult<T> = Result<T, SdJwtError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SdJwtError {
+    #[error(transparent)]
+    SdCwtError(#[from] SdCwtError),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    Base64Error(#[from] base64::DecodeError),
+    #[error("Invalid SD-JWT serialization")]
+    InvalidSerialization,
+    #[error("Invalid JWS")]
+    InvalidJws,
+    #[error("JWS header {0} does not have the expected value")]
+    InvalidHeader(&'static str),
+    #[error("Claims set must be an object")]
+    InvalidClaimsSet,
+    #[error("Claim {0} has an invalid type")]
+    InvalidClaim(String),
+    #[error("Claim name {0} is reserved")]
+    ReservedClaimName(String),
+    #[error("Claim {0} is set more than once")]
+    DuplicateClaim(String),
+    #[error("Disclosable path {0} does not point to any claim")]
+    InvalidDisclosablePath(ClaimPath),
+    #[error("No disclosure matches claim path {0}")]
+    UnknownClaimPath(ClaimPath),
+    #[error("{path} has no JSON form: {reason}")]
+    NoJsonForm { path: ClaimPath, reason: String },
+    #[error("Invalid disclosure {0}")]
+    InvalidDisclosure(String),
+    #[error("Hash algorithm {0} is not supported")]
+    UnsupportedHashAlgorithm(String),
+    #[error("Digest {0} appears more than once")]
+    DuplicateDigest(String),
+    #[error("Disclosure with digest {0} does not match any redacted claim")]
+    UnreferencedDisclosure(String),
+    #[error("Presentation is not bound to its holder by a key binding JWT")]
+    MissingKeyBinding,
+    #[error("No holder key confirmed in the cnf claim")]
+    MissingHolderKey,
+    #[error("Invalid key binding JWT")]
+    InvalidKeyBinding,
+    #[error("Key binding JWT is intended for audience {0}")]
+    KeyBindingAudienceMismatch(String),
+    #[error("Key binding JWT nonce does not match")]
+    KeyBindingNonceMismatch,
+    #[error("Key binding JWT does not cover this presentation")]
+    KeyBindingHashMismatch,
+}
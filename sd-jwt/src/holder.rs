@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use sd_cwt::ClaimPath;
+use serde_json::Value as JsonValue;
+
+use crate::disclosure::{
+    self, hash_algorithm, redacted_element_digest, Disclosure, SD_ALG_KEY, SD_KEY,
+};
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::jws;
+use crate::SdJwt;
+
+impl SdJwt {
+    /// Lists the disclosures carried after the issuer-signed JWT
+    pub fn disclosures(&self) -> SdJwtResult<Vec<Disclosure>> {
+        let parts = self.parts()?;
+        let payload = jws::claims(parts.jws)?;
+        let hash_alg = hash_algorithm(payload.get(SD_ALG_KEY))?;
+
+        // where each digest is referenced from: the object path of a redacted claim or the path of
+        // a redacted element
+        let mut references = HashMap::new();
+        collect_references(&ClaimPath::root(), &payload, &mut references);
+
+        let decoded = parts
+            .disclosures
+            .iter()
+            .map(|encoded| {
+                let (name, value) = disclosure::decode(encoded)?;
+                Ok((disclosure::digest(hash_alg, encoded), name, value))
+            })
+            .collect::<SdJwtResult<Vec<_>>>()?;
+        let mut paths = vec![None; decoded.len()];
+        // disclosed values can themselves reference other disclosures
+        while paths.iter().any(Option::is_none) {
+            let mut resolved = false;
+            for (i, (digest, name, value)) in decoded.iter().enumerate() {
+                let Some(reference) = paths[i]
+                    .is_none()
+                    .then(|| references.get(digest).cloned())
+                    .flatten()
+                else {
+                    continue;
+                };
+                let path = match name {
+                    Some(name) => reference.join(name.as_str()),
+                    None => reference,
+                };
+                collect_references(&path, value, &mut references);
+                paths[i] = Some(path);
+                resolved = true;
+            }
+            if !resolved {
+                let i = paths.iter().position(Option::is_none).unwrap_or_default();
+                return Err(SdJwtError::UnreferencedDisclosure(decoded[i].0.clone()));
+            }
+        }
+
+        Ok(decoded
+            .into_iter()
+            .zip(paths.into_iter().flatten())
+            .zip(parts.disclosures)
+            .map(|(((_, name, value), path), encoded)| Disclosure {
+                path,
+                encoded: encoded.to_string(),
+                name,
+                value,
+            })
+            .collect())
+    }
+
+    /// Presentation disclosing only the claims and array elements designated by `paths`, along
+    /// with their redacted ancestors. Any key binding JWT is dropped.
+    pub fn present(&self, paths: &[ClaimPath]) -> SdJwtResult<SdJwt> {
+        let disclosures = self.disclosures()?;
+        if let Some(unknown) = paths.iter().find(|path| {
+            !disclosures
+                .iter()
+                .any(|d| d.path.len() == path.len() && disclosure::starts_with(path, &d.path))
+        }) {
+            return Err(SdJwtError::UnknownClaimPath(unknown.clone()));
+        }
+        self.present_with(|d| {
+            paths
+                .iter()
+                .any(|path| disclosure::starts_with(path, &d.path))
+        })
+    }
+
+    /// Presentation keeping only the disclosures satisfying `predicate`
+    pub fn present_with(&self, predicate: impl Fn(&Disclosure) -> bool) -> SdJwtResult<SdJwt> {
+        let disclosures = self.disclosures()?;
+        let kept = disclosures
+            .iter()
+            .filter(|d| predicate(d))
+            .map(|d| d.encoded.as_str())
+            .collect::<Vec<_>>();
+        Ok(SdJwt::from_parts(self.parts()?.jws, &kept))
+    }
+}
+
+/// Records the path of every redacted element within `value` and the object path of every
+/// redacted claim
+fn collect_references(
+    path: &ClaimPath,
+    value: &JsonValue,
+    references: &mut HashMap<String, ClaimPath>,
+) {
+    match value {
+        JsonValue::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                let path = path.join(i);
+                match redacted_element_digest(element) {
+                    Some(digest) => {
+                        references.insert(digest.to_string(), path);
+                    }
+                    None => collect_references(&path, element, references),
+                }
+            }
+        }
+        JsonValue::Object(entries) => {
+            for (key, value) in entries {
+                if key == SD_KEY {
+                    for digest in value.as_array().into_iter().flatten() {
+                        if let Some(digest) = digest.as_str() {
+                            references.insert(digest.to_string(), path.clone());
+                        }
+                    }
+                    continue;
+                }
+                collect_references(&path.join(key.as_str()), value, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IssuerPrivateKey;
+
+    const YAML: &str = r#"
+        aud: https://verifier.example
+        !sd given_name: John
+        !sd family_name: Doe
+        !sd address:
+            !sd street_address: "123 Main St"
+            country: US
+        nationalities:
+            - !sd US
+            - !sd DE
+    "#;
+
+    fn issue() -> (IssuerPrivateKey, SdJwt) {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_jwt = issuer.sign(YAML.parse().unwrap()).unwrap();
+        (issuer, sd_jwt)
+    }
+
+    fn paths(paths: &[&str]) -> Vec<ClaimPath> {
+        paths.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn should_list_disclosures() {
+        let (_, sd_jwt) = issue();
+        let mut disclosed = sd_jwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path.to_string())
+            .collect::<Vec<_>>();
+        disclosed.sort();
+        assert_eq!(
+            disclosed,
+            [
+                "/address",
+                "/address/street_address",
+                "/family_name",
+                "/given_name",
+                "/nationalities/0",
+                "/nationalities/1"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_present_with_ancestors() {
+        let (issuer, sd_jwt) = issue();
+        let presentation = sd_jwt
+            .present(&paths(&["/given_name", "/address/street_address"]))
+            .unwrap();
+        assert_eq!(presentation.parts().unwrap().disclosures.len(), 3);
+
+        let claims = issuer.public_key().verify(&presentation).unwrap();
+        assert_eq!(claims["given_name"], "John");
+        assert_eq!(claims["address"]["street_address"], "123 Main St");
+        assert!(claims.get("family_name").is_none());
+        assert_eq!(claims["nationalities"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn should_present_with_predicate() {
+        let (_, sd_jwt) = issue();
+        let presentation = sd_jwt
+            .present_with(|d| d.name.is_none() && d.value == "DE")
+            .unwrap();
+        let disclosures = presentation.disclosures().unwrap();
+        assert_eq!(disclosures.len(), 1);
+        assert_eq!(disclosures[0].path, "/nationalities/1");
+    }
+
+    #[test]
+    fn should_present_numeric_keys() {
+        let issuer = IssuerPrivateKey::generate();
+        let yaml = r#"
+            aud: https://verifier.example
+            !sd "3": three
+            !sd "4": four
+        "#;
+        let sd_jwt = issuer.sign(yaml.parse().unwrap()).unwrap();
+        let mut disclosed = sd_jwt
+            .disclosures()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path.to_string())
+            .collect::<Vec<_>>();
+        disclosed.sort();
+        assert_eq!(disclosed, ["/~23", "/~24"]);
+
+        // object keys being text, a JSON pointer designates them as well
+        let presentation = sd_jwt.present(&paths(&["/~23", "/4"])).unwrap();
+        assert_eq!(presentation.parts().unwrap().disclosures.len(), 2);
+        let claims = issuer.public_key().verify(&presentation).unwrap();
+        assert_eq!(claims["3"], "three");
+        assert_eq!(claims["4"], "four");
+    }
+
+    #[test]
+    fn should_fail_presenting_unknown_path() {
+        let (_, sd_jwt) = issue();
+        assert!(matches!(
+            sd_jwt.present(&paths(&["/address/country"])),
+            Err(SdJwtError::UnknownClaimPath(p)) if p == "/address/country"
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+use itertools::Itertools;
+use sd_cwt::input::InputClaims;
+use sd_cwt::validation::{Clock, SystemClock};
+use sd_cwt::{ClaimPath, HashAlgorithm, PathSegment, SigningAlgorithm, SigningKey};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::disclosure::{
+    self, hash_algorithm, is_key, sd_alg_name, REDACTED_ELEMENT_KEY, SD_ALG_KEY, SD_KEY,
+};
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::jwk::{to_jwk, CNF_JWK_KEY};
+use crate::jws;
+use crate::verifier::VerifierPublicKey;
+use crate::SdJwt;
+
+/// Type of the issuer-signed JWT, advertised in its `typ` header
+pub const SD_JWT_TYP: &str = "dc+sd-jwt";
+
+/// Key of the issuer, signing SD-JWTs with the `alg` matching its [SigningAlgorithm]
+pub struct IssuerPrivateKey {
+    key: SigningKey,
+}
+
+impl IssuerPrivateKey {
+    pub fn new(key: impl Into<SigningKey>) -> Self {
+        Self { key: key.into() }
+    }
+
+    /// Generates an EdDSA key
+    pub fn generate() -> Self {
+        Self::generate_with_algorithm(SigningAlgorithm::EdDsa)
+    }
+
+    pub fn generate_with_algorithm(alg: SigningAlgorithm) -> Self {
+        Self::new(SigningKey::generate(alg))
+    }
+
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        self.key.algorithm()
+    }
+
+    pub fn public_key(&self) -> VerifierPublicKey {
+        VerifierPublicKey::from(self.key.public_key())
+    }
+
+    /// Salts & hashes every disclosable claim then signs the redacted claims set as a JWS. The
+    /// same [InputClaims] as for an SD-CWT are accepted, standard claims being named as in a JWT
+    /// (e.g. `aud`). When absent, `iat` is set to the current time. Disclosures are hashed with
+    /// the algorithm named in `_sd_alg`, SHA-256 by default.
+    pub fn sign(&self, claims: InputClaims) -> SdJwtResult<SdJwt> {
+        let claims_set = claims.to_json()?;
+        self.sign_claims_set(claims_set, &claims.disclosable_paths)
+    }
+
+    /// Same as [Self::sign] but also confirms the holder key, as a JWK, in the `cnf` claim so that
+    /// the holder can later bind its presentations with a key binding JWT. Fails when the claims
+    /// already have a `cnf`.
+    pub fn sign_with_holder_key(
+        &self,
+        claims: InputClaims,
+        holder_key: &sd_cwt::VerifierPublicKey,
+    ) -> SdJwtResult<SdJwt> {
+        let mut claims_set = claims.to_json()?;
+        let entries = claims_set
+            .as_object_mut()
+            .ok_or(SdJwtError::InvalidClaimsSet)?;
+        if entries.contains_key("cnf") {
+            return Err(SdJwtError::DuplicateClaim("cnf".to_string()));
+        }
+        entries.insert(
+            "cnf".to_string(),
+            serde_json::json!({ CNF_JWK_KEY: to_jwk(holder_key) }),
+        );
+        self.sign_claims_set(claims_set, &claims.disclosable_paths)
+    }
+
+    fn sign_claims_set(
+        &self,
+        mut claims_set: JsonValue,
+        paths: &[ClaimPath],
+    ) -> SdJwtResult<SdJwt> {
+        let entries = claims_set
+            .as_object_mut()
+            .ok_or(SdJwtError::InvalidClaimsSet)?;
+        let hash_alg = hash_algorithm(entries.get(SD_ALG_KEY))?;
+        entries.insert(SD_ALG_KEY.to_string(), sd_alg_name(hash_alg).into());
+        if !entries.contains_key("iat") {
+            entries.insert("iat".to_string(), now()?.into());
+        }
+
+        let disclosures = redact(&mut claims_set, paths, hash_alg)?;
+        let jws = jws::sign(&self.key, SD_JWT_TYP, &claims_set)?;
+        Ok(SdJwt::from_parts(
+            &jws,
+            &disclosures.iter().map(String::as_str).collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// Replaces every disclosable claim and array element by the digest of its salted disclosure,
+/// added to the `_sd` array of its object or in place of the element i.e. `{ "...": digest }`.
+/// Returns the encoded disclosures.
+fn redact(
+    claims_set: &mut JsonValue,
+    paths: &[ClaimPath],
+    hash_alg: HashAlgorithm,
+) -> SdJwtResult<Vec<String>> {
+    let mut disclosures = vec![];
+    // redact the deepest claims first so that their digests end up in their (maybe redacted) parent
+    let paths = paths
+        .iter()
+        .sorted_by_key(|path| std::cmp::Reverse(path.len()));
+
+    for path in paths {
+        let invalid_path = || SdJwtError::InvalidDisclosablePath(path.clone());
+        let (name, parents) = path.segments().split_last().ok_or_else(invalid_path)?;
+
+        match lookup_mut(claims_set, parents).ok_or_else(invalid_path)? {
+            JsonValue::Object(entries) => {
+                let key = entries
+                    .keys()
+                    .find(|k| is_key(name, k))
+                    .cloned()
+                    .ok_or_else(invalid_path)?;
+                if key == SD_KEY || key == REDACTED_ELEMENT_KEY || key == SD_ALG_KEY {
+                    return Err(SdJwtError::ReservedClaimName(key));
+                }
+                let value = entries.shift_remove(&key).ok_or_else(invalid_path)?;
+                let encoded = disclosure::encode(Some(&key), value)?;
+                push_digest(entries, disclosure::digest(hash_alg, &encoded))?;
+                disclosures.push(encoded);
+            }
+            JsonValue::Array(elements) => {
+                let element = name
+                    .index()
+                    .and_then(|i| elements.get_mut(i))
+                    .ok_or_else(invalid_path)?;
+                let encoded = disclosure::encode(None, element.take())?;
+                let digest = disclosure::digest(hash_alg, &encoded);
+                *element = serde_json::json!({ REDACTED_ELEMENT_KEY: digest });
+                disclosures.push(encoded);
+            }
+            _ => return Err(invalid_path()),
+        }
+    }
+    Ok(disclosures)
+}
+
+/// Claim, object entry or array element designated by the path segments
+fn lookup_mut<'a>(
+    claims_set: &'a mut JsonValue,
+    segments: &[PathSegment],
+) -> Option<&'a mut JsonValue> {
+    segments
+        .iter()
+        .try_fold(claims_set, |parent, segment| match parent {
+            JsonValue::Object(entries) => entries
+                .iter_mut()
+                .find_map(|(k, v)| is_key(segment, k).then_some(v)),
+            JsonValue::Array(elements) => segment.index().and_then(|i| elements.get_mut(i)),
+            _ => None,
+        })
+}
+
+/// Adds a digest to the `_sd` array of an object, kept sorted so that the order of the digests
+/// does not leak the order of the claims
+fn push_digest(entries: &mut Map<String, JsonValue>, digest: String) -> SdJwtResult<()> {
+    let digests = entries
+        .entry(SD_KEY)
+        .or_insert_with(|| JsonValue::Array(vec![]))
+        .as_array_mut()
+        .ok_or_else(|| SdJwtError::InvalidClaim(SD_KEY.to_string()))?;
+    let position = digests.partition_point(|d| d.as_str() < Some(digest.as_str()));
+    digests.insert(position, digest.into());
+    Ok(())
+}
+
+/// Current time of the system clock, failing as for an SD-CWT when it is before the Unix epoch
+pub(crate) fn now() -> SdJwtResult<i64> {
+    Ok(SystemClock.now()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jws::claims;
+
+    const YAML: &str = r#"
+        iss: https://issuer.example
+        aud: https://verifier.example
+        !sd given_name: John
+        !sd address:
+            !sd street_address: "123 Main St"
+            country: US
+        nationalities:
+            - !sd US
+            - DE
+    "#;
+
+    #[test]
+    fn should_sign() {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_jwt = issuer.sign(YAML.parse().unwrap()).unwrap();
+        let parts = sd_jwt.parts().unwrap();
+        assert_eq!(parts.disclosures.len(), 4);
+        assert!(parts.kb_jwt.is_none());
+        assert!(sd_jwt.as_str().ends_with('~'));
+
+        let payload = claims(parts.jws).unwrap();
+        assert_eq!(payload[SD_ALG_KEY], "sha-256");
+        assert_eq!(payload["iss"], "https://issuer.example");
+        assert!(payload["iat"].is_i64());
+        assert!(payload.get("given_name").is_none());
+        assert!(payload.get("address").is_none());
+        assert_eq!(payload[SD_KEY].as_array().unwrap().len(), 2);
+        let nationalities = payload["nationalities"].as_array().unwrap();
+        assert!(disclosure::redacted_element_digest(&nationalities[0]).is_some());
+        assert_eq!(nationalities[1], "DE");
+
+        // the nested claim is redacted within its disclosed parent
+        let (name, address) = parts
+            .disclosures
+            .iter()
+            .map(|d| disclosure::decode(d).unwrap())
+            .find(|(name, _)| name.as_deref() == Some("address"))
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("address"));
+        assert_eq!(address["country"], "US");
+        assert_eq!(address[SD_KEY].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_hash_with_sd_alg() {
+        let issuer = IssuerPrivateKey::generate_with_algorithm(SigningAlgorithm::Es256);
+        let input = "_sd_alg: sha-384\n!sd given_name: John".parse().unwrap();
+        let sd_jwt = issuer.sign(input).unwrap();
+        let parts = sd_jwt.parts().unwrap();
+        let payload = claims(parts.jws).unwrap();
+        assert_eq!(payload[SD_ALG_KEY], "sha-384");
+        assert_eq!(
+            payload[SD_KEY][0],
+            disclosure::digest(HashAlgorithm::Sha384, parts.disclosures[0])
+        );
+    }
+
+    #[test]
+    fn should_fail_with_reserved_or_unknown_claims() {
+        let issuer = IssuerPrivateKey::generate();
+        assert!(matches!(
+            issuer.sign("!sd _sd_alg: sha-256".parse().unwrap()),
+            Err(SdJwtError::ReservedClaimName(name)) if name == SD_ALG_KEY
+        ));
+        let mut input = "given_name: John".parse::<InputClaims>().unwrap();
+        input.disclosable_paths = vec!["/family_name".parse().unwrap()];
+        assert!(matches!(
+            issuer.sign(input),
+            Err(SdJwtError::InvalidDisclosablePath(p)) if p == "/family_name"
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_existing_cnf() {
+        let issuer = IssuerPrivateKey::generate();
+        let holder = SigningKey::generate(SigningAlgorithm::EdDsa);
+        assert!(matches!(
+            issuer.sign_with_holder_key("cnf: {}".parse().unwrap(), &holder.public_key()),
+            Err(SdJwtError::DuplicateClaim(name)) if name == "cnf"
+        ));
+    }
+}
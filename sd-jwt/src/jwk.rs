@@ -0,0 +1,81 @@
+use sd_cwt::cose_key::{
+    CoseKey, CoseKeyParams, COSE_CRV_ED25519, COSE_CRV_P256, COSE_CRV_P384, COSE_CRV_P521,
+};
+use serde_json::Value as JsonValue;
+
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::jws::{b64, b64_decode};
+
+/// Key of the JWK within the confirmation claim
+pub const CNF_JWK_KEY: &str = "jwk";
+
+const CURVES: [(i64, &str); 4] = [
+    (COSE_CRV_ED25519, "Ed25519"),
+    (COSE_CRV_P256, "P-256"),
+    (COSE_CRV_P384, "P-384"),
+    (COSE_CRV_P521, "P-521"),
+];
+
+/// Encodes a public key as an OKP or EC JWK (RFC 7517), as expected in the `cnf` claim
+pub fn to_jwk(key: &sd_cwt::VerifierPublicKey) -> JsonValue {
+    let crv_name = |crv: i64| CURVES.iter().find(|(c, _)| *c == crv).map(|(_, n)| *n);
+    match CoseKey::from(key).params {
+        CoseKeyParams::Okp { crv, x } => serde_json::json!({
+            "kty": "OKP",
+            "crv": crv_name(crv),
+            "x": b64(x),
+        }),
+        CoseKeyParams::Ec2 { crv, x, y } => serde_json::json!({
+            "kty": "EC",
+            "crv": crv_name(crv),
+            "x": b64(x),
+            "y": b64(y),
+        }),
+        // public keys are never symmetric
+        CoseKeyParams::Symmetric { .. } => JsonValue::Null,
+    }
+}
+
+/// Decodes an Ed25519 OKP JWK or a P-256, P-384 or P-521 EC one
+pub fn from_jwk(jwk: &JsonValue) -> SdJwtResult<sd_cwt::VerifierPublicKey> {
+    let invalid = || SdJwtError::InvalidClaim(CNF_JWK_KEY.to_string());
+    let param = |name: &str| jwk[name].as_str().ok_or_else(invalid);
+    let crv = param("crv")?;
+    let crv = CURVES
+        .iter()
+        .find(|(_, n)| *n == crv)
+        .map(|(c, _)| *c)
+        .ok_or_else(invalid)?;
+    let x = b64_decode(param("x")?)?;
+    let params = match param("kty")? {
+        "OKP" => CoseKeyParams::Okp { crv, x },
+        "EC" => CoseKeyParams::Ec2 {
+            crv,
+            x,
+            y: b64_decode(param("y")?)?,
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(CoseKey::new(params).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sd_cwt::{SigningAlgorithm, SigningKey};
+
+    #[test]
+    fn should_round_trip() {
+        for alg in [
+            SigningAlgorithm::EdDsa,
+            SigningAlgorithm::Es256,
+            SigningAlgorithm::Es384,
+            SigningAlgorithm::Es512,
+        ] {
+            let key = SigningKey::generate(alg).public_key();
+            let pk = from_jwk(&to_jwk(&key)).unwrap();
+            assert_eq!(pk.to_bytes(), key.to_bytes());
+        }
+        assert!(from_jwk(&serde_json::json!({ "kty": "RSA" })).is_err());
+    }
+}
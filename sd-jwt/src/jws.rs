@@ -0,0 +1,65 @@
+use base64::prelude::*;
+use sd_cwt::{SigningAlgorithm, SigningKey};
+use serde_json::Value as JsonValue;
+
+use crate::error::{SdJwtError, SdJwtResult};
+
+/// JWS `alg` of a signing algorithm
+pub(crate) fn alg_name(alg: SigningAlgorithm) -> &'static str {
+    match alg {
+        SigningAlgorithm::EdDsa => "EdDSA",
+        SigningAlgorithm::Es256 => "ES256",
+        SigningAlgorithm::Es384 => "ES384",
+        SigningAlgorithm::Es512 => "ES512",
+    }
+}
+
+pub(crate) fn b64(bytes: impl AsRef<[u8]>) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub(crate) fn b64_decode(encoded: &str) -> SdJwtResult<Vec<u8>> {
+    Ok(BASE64_URL_SAFE_NO_PAD.decode(encoded)?)
+}
+
+/// Signs `claims` as a compact JWS whose protected header holds the `alg` of the key and `typ`
+pub(crate) fn sign(key: &SigningKey, typ: &str, claims: &JsonValue) -> SdJwtResult<String> {
+    let header = serde_json::json!({ "alg": alg_name(key.algorithm()), "typ": typ });
+    let signing_input = format!(
+        "{}.{}",
+        b64(serde_json::to_vec(&header)?),
+        b64(serde_json::to_vec(claims)?)
+    );
+    let signature = key.sign(signing_input.as_bytes());
+    Ok(format!("{signing_input}.{}", b64(signature)))
+}
+
+/// Verifies a compact JWS against the key, expecting it to be of type `typ`, and returns its
+/// claims
+pub(crate) fn verify(
+    key: &sd_cwt::VerifierPublicKey,
+    typ: &str,
+    jws: &str,
+) -> SdJwtResult<JsonValue> {
+    let (signing_input, signature) = jws.rsplit_once('.').ok_or(SdJwtError::InvalidJws)?;
+    let (header, _) = signing_input
+        .split_once('.')
+        .ok_or(SdJwtError::InvalidJws)?;
+    let header = serde_json::from_slice::<JsonValue>(&b64_decode(header)?)?;
+    if header["alg"] != alg_name(key.algorithm()) {
+        return Err(SdJwtError::InvalidHeader("alg"));
+    }
+    if header["typ"] != typ {
+        return Err(SdJwtError::InvalidHeader("typ"));
+    }
+    key.verify_raw(signing_input.as_bytes(), &b64_decode(signature)?)?;
+    claims(jws)
+}
+
+/// Claims of a compact JWS, without verifying it
+pub(crate) fn claims(jws: &str) -> SdJwtResult<JsonValue> {
+    let [_, payload, _] = jws.split('.').collect::<Vec<_>>()[..] else {
+        return Err(SdJwtError::InvalidJws);
+    };
+    Ok(serde_json::from_slice(&b64_decode(payload)?)?)
+}
@@ -0,0 +1,164 @@
+use sd_cwt::{HashAlgorithm, SigningKey};
+use serde_json::Value as JsonValue;
+
+use crate::disclosure::{hash_algorithm, SD_ALG_KEY};
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::issuer::now;
+use crate::jwk::{from_jwk, CNF_JWK_KEY};
+use crate::jws::{self, b64};
+use crate::{SdJwt, SEPARATOR};
+
+/// Type of a key binding JWT, advertised in its `typ` header
+pub const KB_JWT_TYP: &str = "kb+jwt";
+
+impl SdJwt {
+    /// Binds this presentation to the holder with a key binding JWT signed by the key confirmed
+    /// in the `cnf` claim. The key binding JWT covers the issuer-signed JWT and the disclosures
+    /// through its `sd_hash`. Any previous key binding JWT is replaced.
+    pub fn bind(&self, holder_key: &SigningKey, audience: &str, nonce: &str) -> SdJwtResult<SdJwt> {
+        let presentation = self.without_key_binding()?;
+        let payload = jws::claims(presentation.parts()?.jws)?;
+        let hash_alg = hash_algorithm(payload.get(SD_ALG_KEY))?;
+
+        let claims = serde_json::json!({
+            "iat": now()?,
+            "aud": audience,
+            "nonce": nonce,
+            "sd_hash": sd_hash(hash_alg, &presentation),
+        });
+        let kb_jwt = jws::sign(holder_key, KB_JWT_TYP, &claims)?;
+        Ok(SdJwt(format!("{presentation}{kb_jwt}")))
+    }
+
+    pub(crate) fn without_key_binding(&self) -> SdJwtResult<SdJwt> {
+        let parts = self.parts()?;
+        Ok(SdJwt::from_parts(parts.jws, &parts.disclosures))
+    }
+}
+
+/// Digest of the presentation, without its key binding JWT, as expected in `sd_hash`
+pub(crate) fn sd_hash(hash_alg: HashAlgorithm, presentation: &SdJwt) -> String {
+    debug_assert!(presentation.as_str().ends_with(SEPARATOR));
+    b64(hash_alg.digest(presentation.as_str()))
+}
+
+/// Extracts the holder key confirmed by the issuer in the `cnf` claim
+pub(crate) fn holder_key(claims: &JsonValue) -> SdJwtResult<sd_cwt::VerifierPublicKey> {
+    let jwk = claims
+        .get("cnf")
+        .and_then(|cnf| cnf.get(CNF_JWK_KEY))
+        .ok_or(SdJwtError::MissingHolderKey)?;
+    from_jwk(jwk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IssuerPrivateKey;
+    use sd_cwt::error::SdCwtError;
+    use sd_cwt::{SigningAlgorithm, VerificationOptions};
+    use std::time::Duration;
+
+    const YAML: &str = r#"
+        aud: https://verifier.example
+        !sd given_name: John
+        !sd family_name: Doe
+    "#;
+    const AUDIENCE: &str = "https://verifier.example";
+    const NONCE: &str = "1234567890";
+
+    fn issue(holder: &SigningKey) -> (IssuerPrivateKey, SdJwt) {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_jwt = issuer
+            .sign_with_holder_key(YAML.parse().unwrap(), &holder.public_key())
+            .unwrap();
+        (issuer, sd_jwt)
+    }
+
+    #[test]
+    fn should_bind_presentation() {
+        let holder = SigningKey::generate(SigningAlgorithm::Es256);
+        let (issuer, sd_jwt) = issue(&holder);
+        let presentation = sd_jwt
+            .present(&["/given_name".parse().unwrap()])
+            .unwrap()
+            .bind(&holder, AUDIENCE, NONCE)
+            .unwrap();
+        assert!(presentation.parts().unwrap().kb_jwt.is_some());
+
+        let claims = issuer
+            .public_key()
+            .verify_presentation(&presentation, AUDIENCE, NONCE)
+            .unwrap();
+        assert_eq!(claims["given_name"], "John");
+        assert!(claims.get("family_name").is_none());
+    }
+
+    #[test]
+    fn should_fail_with_other_holder_key() {
+        let holder = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let (issuer, sd_jwt) = issue(&holder);
+        let other = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let presentation = sd_jwt.bind(&other, AUDIENCE, NONCE).unwrap();
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify_presentation(&presentation, AUDIENCE, NONCE),
+            Err(SdJwtError::SdCwtError(_))
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_mismatches() {
+        let holder = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let (issuer, sd_jwt) = issue(&holder);
+        let verifier = issuer.public_key();
+        let presentation = sd_jwt.bind(&holder, AUDIENCE, NONCE).unwrap();
+
+        assert!(matches!(
+            verifier.verify_presentation(&sd_jwt, AUDIENCE, NONCE),
+            Err(SdJwtError::MissingKeyBinding)
+        ));
+        assert!(matches!(
+            verifier.verify_presentation(&presentation, "https://other.example", NONCE),
+            Err(SdJwtError::KeyBindingAudienceMismatch(aud)) if aud == AUDIENCE
+        ));
+        assert!(matches!(
+            verifier.verify_presentation(&presentation, AUDIENCE, "other"),
+            Err(SdJwtError::KeyBindingNonceMismatch)
+        ));
+
+        // a disclosure withheld after binding
+        let kb_jwt = presentation.parts().unwrap().kb_jwt.unwrap().to_string();
+        let withheld = sd_jwt.present(&[]).unwrap();
+        let tampered = SdJwt(format!("{withheld}{kb_jwt}"));
+        assert!(matches!(
+            verifier.verify_presentation(&tampered, AUDIENCE, NONCE),
+            Err(SdJwtError::KeyBindingHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_stale_key_binding_jwt() {
+        let holder = SigningKey::generate(SigningAlgorithm::EdDsa);
+        let (issuer, sd_jwt) = issue(&holder);
+        let presentation = sd_jwt.bind(&holder, AUDIENCE, NONCE).unwrap();
+        let iat = now().unwrap();
+
+        let later = VerificationOptions::default().with_clock(move || iat + 600);
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify_presentation_with(&presentation, AUDIENCE, NONCE, &later),
+            Err(SdJwtError::SdCwtError(SdCwtError::TooOld {
+                max_age: 300,
+                ..
+            }))
+        ));
+        let later = later.with_key_binding_max_age(Duration::from_secs(900));
+        issuer
+            .public_key()
+            .verify_presentation_with(&presentation, AUDIENCE, NONCE, &later)
+            .unwrap();
+    }
+}
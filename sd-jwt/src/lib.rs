@@ -0,0 +1,96 @@
+pub use disclosure::Disclosure;
+pub use issuer::IssuerPrivateKey;
+pub use verifier::VerifierPublicKey;
+
+pub mod error;
+pub mod convert;
+pub mod disclosure;
+pub mod issuer;
+pub mod holder;
+pub mod jwk;
+pub mod kbt;
+pub mod verifier;
+mod jws;
+
+use std::str::FromStr;
+
+use error::{SdJwtError, SdJwtResult};
+
+/// Separator of the issuer-signed JWT, the disclosures and the key binding JWT
+pub const SEPARATOR: char = '~';
+
+/// SD-JWT in its compact serialization i.e. `<issuer-jwt>~<disclosure>~...~<kb-jwt>` where the
+/// key binding JWT is empty when the presentation is not bound
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdJwt(String);
+
+/// Borrowed parts of an [SdJwt]
+pub(crate) struct Parts<'a> {
+    pub(crate) jws: &'a str,
+    pub(crate) disclosures: Vec<&'a str>,
+    pub(crate) kb_jwt: Option<&'a str>,
+}
+
+impl SdJwt {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn parts(&self) -> SdJwtResult<Parts<'_>> {
+        let mut parts = self.0.split(SEPARATOR).collect::<Vec<_>>();
+        if parts.len() < 2 {
+            return Err(SdJwtError::InvalidSerialization);
+        }
+        let kb_jwt = parts.pop().filter(|kb_jwt| !kb_jwt.is_empty());
+        let jws = parts.remove(0);
+        if jws.is_empty() || parts.iter().any(|d| d.is_empty()) {
+            return Err(SdJwtError::InvalidSerialization);
+        }
+        Ok(Parts {
+            jws,
+            disclosures: parts,
+            kb_jwt,
+        })
+    }
+
+    /// Serialization without the key binding JWT, as covered by its `sd_hash`
+    pub(crate) fn from_parts(jws: &str, disclosures: &[&str]) -> Self {
+        let mut sd_jwt = String::from(jws);
+        for disclosure in disclosures {
+            sd_jwt.push(SEPARATOR);
+            sd_jwt.push_str(disclosure);
+        }
+        sd_jwt.push(SEPARATOR);
+        Self(sd_jwt)
+    }
+}
+
+impl FromStr for SdJwt {
+    type Err = SdJwtError;
+
+    fn from_str(s: &str) -> SdJwtResult<Self> {
+        let sd_jwt = Self(s.to_string());
+        sd_jwt.parts()?;
+        Ok(sd_jwt)
+    }
+}
+
+impl std::fmt::Display for SdJwt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sd_cwt::input::InputClaims;
+
+    #[test]
+    fn should_succeed() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = "aud: https://verifier.example".parse::<InputClaims>().unwrap();
+        let sd_jwt = issuer.sign(input).unwrap();
+        assert_eq!(sd_jwt.to_string().parse::<SdJwt>().unwrap(), sd_jwt);
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+
+use sd_cwt::{RegisteredClaims, VerificationOptions};
+use serde_json::Value as JsonValue;
+
+use crate::disclosure::{
+    self, hash_algorithm, redacted_element_digest, REDACTED_ELEMENT_KEY, SD_ALG_KEY, SD_KEY,
+};
+use crate::error::{SdJwtError, SdJwtResult};
+use crate::issuer::SD_JWT_TYP;
+use crate::jws;
+use crate::kbt::{self, KB_JWT_TYP};
+use crate::SdJwt;
+
+/// Public key of the issuer, verifying the SD-JWTs it signed
+pub struct VerifierPublicKey(sd_cwt::VerifierPublicKey);
+
+impl From<sd_cwt::VerifierPublicKey> for VerifierPublicKey {
+    fn from(key: sd_cwt::VerifierPublicKey) -> Self {
+        Self(key)
+    }
+}
+
+impl VerifierPublicKey {
+    /// Verifies the issuer signature then matches every disclosure against the `_sd` digests and
+    /// the redacted elements, recursively since disclosed values can themselves have redacted
+    /// claims or elements. Every disclosure must be referenced exactly once. Returns the claims
+    /// set where the disclosed claims and elements have been merged back and from which the
+    /// digests and `_sd_alg` have been removed. Any key binding JWT is ignored. The `exp`, `nbf`
+    /// and `iat` claims are checked against the system clock.
+    pub fn verify(&self, sd_jwt: &SdJwt) -> SdJwtResult<JsonValue> {
+        self.verify_with(sd_jwt, &VerificationOptions::default())
+    }
+
+    /// Same as [Self::verify] but checks the `exp`, `nbf`, `iat`, `aud` and `iss` claims
+    /// according to `options`
+    pub fn verify_with(
+        &self,
+        sd_jwt: &SdJwt,
+        options: &VerificationOptions,
+    ) -> SdJwtResult<JsonValue> {
+        let parts = sd_jwt.parts()?;
+        let mut claims = jws::verify(&self.0, SD_JWT_TYP, parts.jws)?;
+        let entries = claims.as_object_mut().ok_or(SdJwtError::InvalidClaimsSet)?;
+        let hash_alg = hash_algorithm(entries.shift_remove(SD_ALG_KEY).as_ref())?;
+
+        let mut disclosed = Disclosed::default();
+        for encoded in parts.disclosures {
+            let digest = disclosure::digest(hash_alg, encoded);
+            let decoded = disclosure::decode(encoded)?;
+            if disclosed
+                .by_digest
+                .insert(digest.clone(), decoded)
+                .is_some()
+            {
+                return Err(SdJwtError::DuplicateDigest(digest));
+            }
+        }
+        disclosed.merge(&mut claims)?;
+        if let Some(digest) = disclosed.by_digest.into_keys().next() {
+            return Err(SdJwtError::UnreferencedDisclosure(digest));
+        }
+        // disclosed registered claims are checked as well
+        options.validate_claims(&registered_claims(&claims)?)?;
+        Ok(claims)
+    }
+
+    /// Same as [Self::verify] but also requires a key binding JWT signed by the holder key
+    /// confirmed in the `cnf` claim, intended for `audience`, carrying `nonce` and covering this
+    /// very presentation, issued at most [sd_cwt::validation::DEFAULT_KEY_BINDING_MAX_AGE] ago
+    pub fn verify_presentation(
+        &self,
+        sd_jwt: &SdJwt,
+        audience: &str,
+        nonce: &str,
+    ) -> SdJwtResult<JsonValue> {
+        self.verify_presentation_with(sd_jwt, audience, nonce, &VerificationOptions::default())
+    }
+
+    /// Same as [Self::verify_presentation] but checks the claims of the SD-JWT and the `iat` of
+    /// the key binding JWT according to `options`
+    pub fn verify_presentation_with(
+        &self,
+        sd_jwt: &SdJwt,
+        audience: &str,
+        nonce: &str,
+        options: &VerificationOptions,
+    ) -> SdJwtResult<JsonValue> {
+        let claims = self.verify_with(sd_jwt, options)?;
+        let kb_jwt = sd_jwt
+            .parts()?
+            .kb_jwt
+            .ok_or(SdJwtError::MissingKeyBinding)?;
+
+        let holder_key = kbt::holder_key(&claims)?;
+        let kb_claims = jws::verify(&holder_key, KB_JWT_TYP, kb_jwt)?;
+        let iat = kb_claims["iat"]
+            .as_i64()
+            .ok_or(SdJwtError::InvalidKeyBinding)?;
+        let aud = kb_claims["aud"]
+            .as_str()
+            .ok_or(SdJwtError::InvalidKeyBinding)?;
+        if aud != audience {
+            return Err(SdJwtError::KeyBindingAudienceMismatch(aud.to_string()));
+        }
+        if kb_claims["nonce"] != nonce {
+            return Err(SdJwtError::KeyBindingNonceMismatch);
+        }
+
+        let payload = jws::claims(sd_jwt.parts()?.jws)?;
+        let hash_alg = hash_algorithm(payload.get(SD_ALG_KEY))?;
+        let sd_hash = kbt::sd_hash(hash_alg, &sd_jwt.without_key_binding()?);
+        if kb_claims["sd_hash"] != sd_hash {
+            return Err(SdJwtError::KeyBindingHashMismatch);
+        }
+        options.validate_key_binding_iat(iat)?;
+        Ok(claims)
+    }
+}
+
+/// Registered claims of a verified claims set, where `aud` is either a string or an array of them
+fn registered_claims(claims: &JsonValue) -> SdJwtResult<RegisteredClaims<'_>> {
+    let invalid = |name: &str| SdJwtError::InvalidClaim(name.to_string());
+    let text = |name| {
+        claims
+            .get(name)
+            .map(|value| value.as_str().ok_or_else(|| invalid(name)))
+            .transpose()
+    };
+    let time = |name| {
+        claims
+            .get(name)
+            .map(|value| value.as_i64().ok_or_else(|| invalid(name)))
+            .transpose()
+    };
+    let aud = match claims.get("aud") {
+        Some(JsonValue::Array(audiences)) => audiences
+            .iter()
+            .map(|aud| aud.as_str().ok_or_else(|| invalid("aud")))
+            .collect::<SdJwtResult<_>>()?,
+        _ => text("aud")?.into_iter().collect(),
+    };
+    Ok(RegisteredClaims {
+        iss: text("iss")?,
+        aud,
+        exp: time("exp")?,
+        nbf: time("nbf")?,
+        iat: time("iat")?,
+    })
+}
+
+/// Disclosures of an SD-JWT by digest, each of which must be referenced exactly once from the
+/// claims set or from another disclosure
+#[derive(Default)]
+struct Disclosed {
+    by_digest: HashMap<String, (Option<String>, JsonValue)>,
+    seen: HashSet<String>,
+}
+
+impl Disclosed {
+    fn see(&mut self, digest: &str) -> SdJwtResult<()> {
+        if !self.seen.insert(digest.to_string()) {
+            return Err(SdJwtError::DuplicateDigest(digest.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Replaces, within `value`, the digests by the claims and elements they disclose
+    fn merge(&mut self, value: &mut JsonValue) -> SdJwtResult<()> {
+        match value {
+            JsonValue::Object(entries) => {
+                for value in entries.values_mut() {
+                    self.merge(value)?;
+                }
+                let Some(digests) = entries.shift_remove(SD_KEY) else {
+                    return Ok(());
+                };
+                let invalid = || SdJwtError::InvalidClaim(SD_KEY.to_string());
+                for digest in digests.as_array().ok_or_else(invalid)? {
+                    let digest = digest.as_str().ok_or_else(invalid)?;
+                    self.see(digest)?;
+                    let Some((name, mut value)) = self.by_digest.remove(digest) else {
+                        // decoy or withheld claim
+                        continue;
+                    };
+                    let name = name.ok_or_else(|| SdJwtError::InvalidDisclosure(digest.into()))?;
+                    if entries.contains_key(&name) {
+                        return Err(SdJwtError::DuplicateClaim(name));
+                    }
+                    self.merge(&mut value)?;
+                    entries.insert(name, value);
+                }
+            }
+            JsonValue::Array(elements) => {
+                let mut merged = Vec::with_capacity(elements.len());
+                for mut element in std::mem::take(elements) {
+                    let Some(digest) = redacted_element_digest(&element).map(str::to_string) else {
+                        if element.get(REDACTED_ELEMENT_KEY).is_some() {
+                            return Err(SdJwtError::InvalidClaim(REDACTED_ELEMENT_KEY.into()));
+                        }
+                        self.merge(&mut element)?;
+                        merged.push(element);
+                        continue;
+                    };
+                    self.see(&digest)?;
+                    let Some((name, mut value)) = self.by_digest.remove(&digest) else {
+                        continue;
+                    };
+                    if name.is_some() {
+                        return Err(SdJwtError::InvalidDisclosure(digest));
+                    }
+                    self.merge(&mut value)?;
+                    merged.push(value);
+                }
+                *elements = merged;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IssuerPrivateKey;
+    use sd_cwt::error::SdCwtError;
+    use sd_cwt::input::InputClaims;
+    use sd_cwt::Accepted;
+
+    const YAML: &str = r#"
+        iss: https://issuer.example
+        !sd given_name: John
+        !sd address:
+            !sd street_address: "123 Main St"
+            country: US
+        nationalities:
+            - !sd US
+            - DE
+    "#;
+
+    #[test]
+    fn should_verify() {
+        let issuer = IssuerPrivateKey::generate();
+        let input = YAML.parse::<InputClaims>().unwrap();
+        let sd_jwt = issuer.sign(input.clone()).unwrap();
+        let mut claims = issuer.public_key().verify(&sd_jwt).unwrap();
+        assert!(claims.as_object_mut().unwrap().remove("iat").is_some());
+        // same claims set as the one input
+        assert_eq!(claims, input.to_json().unwrap());
+    }
+
+    #[test]
+    fn should_fail_with_other_issuer() {
+        let sd_jwt = IssuerPrivateKey::generate()
+            .sign(YAML.parse().unwrap())
+            .unwrap();
+        assert!(IssuerPrivateKey::generate()
+            .public_key()
+            .verify(&sd_jwt)
+            .is_err());
+    }
+
+    #[test]
+    fn should_check_times_and_parties() {
+        const NOW: i64 = 1_700_000_000;
+        let issuer = IssuerPrivateKey::generate();
+        let input = r#"
+            iss: https://issuer.example
+            aud: [https://other.example, https://verifier.example]
+            iat: 1699999000
+            !sd exp: 1700000001
+        "#;
+        let sd_jwt = issuer.sign(input.parse().unwrap()).unwrap();
+        // disclosed registered claims are checked as well
+        assert!(matches!(
+            issuer.public_key().verify(&sd_jwt),
+            Err(SdJwtError::SdCwtError(SdCwtError::Expired { exp, .. })) if exp == NOW + 1
+        ));
+
+        let options = VerificationOptions::default().with_clock(|| NOW);
+        let tenant_a = options
+            .clone()
+            .accept_audience(Accepted::exact("https://verifier.example"))
+            .accept_issuer(Accepted::pattern("https://*.example"));
+        issuer.public_key().verify_with(&sd_jwt, &tenant_a).unwrap();
+
+        let tenant_b = options
+            .clone()
+            .accept_audience(Accepted::exact("https://tenant-b.example"));
+        assert!(matches!(
+            issuer.public_key().verify_with(&sd_jwt, &tenant_b),
+            Err(SdJwtError::SdCwtError(SdCwtError::UnacceptedAudience(_)))
+        ));
+        let too_old = options.with_max_age(std::time::Duration::from_secs(60));
+        assert!(matches!(
+            issuer.public_key().verify_with(&sd_jwt, &too_old),
+            Err(SdJwtError::SdCwtError(SdCwtError::TooOld { .. }))
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_unreferenced_or_duplicate_disclosure() {
+        let issuer = IssuerPrivateKey::generate();
+        let sd_jwt = issuer.sign(YAML.parse().unwrap()).unwrap();
+        let parts = sd_jwt.parts().unwrap();
+
+        let foreign = disclosure::encode(Some("family_name"), "Doe".into()).unwrap();
+        let mut disclosures = parts.disclosures.clone();
+        disclosures.push(&foreign);
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify(&SdJwt::from_parts(parts.jws, &disclosures)),
+            Err(SdJwtError::UnreferencedDisclosure(_))
+        ));
+
+        let mut disclosures = parts.disclosures.clone();
+        disclosures.push(parts.disclosures[0]);
+        assert!(matches!(
+            issuer
+                .public_key()
+                .verify(&SdJwt::from_parts(parts.jws, &disclosures)),
+            Err(SdJwtError::DuplicateDigest(_))
+        ));
+    }
+}